@@ -7,19 +7,28 @@ function mlb_auto_complete {
     reply=""
 
     if [[ $COMP_CWORD = 1 ]]; then
-        reply="games results schedule stats teams leaders league-batting-stats league-pitching-stats standings update"
+        reply="games lineup scoreboard results schedule stats compare teams leaders league-batting-stats league-pitching-stats standings bracket parks update"
     elif [[ $COMP_CWORD = 2 ]]; then
         case ${COMP_WORDS[1]} in
-            "g" | "games" | "r" | "results" | "u" | "schedule" | "t" | "teams")
+            "g" | "games" | "lu" | "lineup" | "r" | "results" | "u" | "schedule")
                 read_file "teams"
                 reply=$keys;;
-            "s" | "stats")
+            "t" | "teams")
+                read_file "teams"
+                reply="$keys --all";;
+            "s" | "stats" | "c" | "compare")
                 read_file "players"
                 reply=$keys;;
             "l" | "leaders")
-                reply="b p avg hr rbi h sb wins era saves so whip all";;
+                reply="b p list avg obp slg ops hr rbi h r 2b 3b tb bb k sb cs xbh hbp pa wins w l era saves sv hld so whip ip k9 bb9 h9 hr9 kbb gs cg sho pbb phr all";;
             "b" | "league-batting-stats" | "p" | "league-pitching-stats")
-                reply="all-time";;
+                reply="all-time --source --season --from --to --cols --chart --eras";;
+            "sb" | "scoreboard")
+                reply="--game-type";;
+            "parks" | "bracket")
+                reply="--season";;
+            "standings")
+                reply="overall wildcard playoffs odds trend --season --date --columns --sort --league --division --metric";;
             "update")
                 reply="players teams";;
         esac
@@ -34,10 +43,33 @@ function mlb_auto_complete {
             fi
         elif [[ $query_type == "t" || $query_type == "teams" ]]; then
             read_file "teams"
-            if [[ $keys =~ $input ]]; then
+            if [[ $keys =~ $input || $input == " --all " ]]; then
                 reply="hitting pitching"
             fi
+        elif [[ $query_type == "c" || $query_type == "compare" ]]; then
+            read_file "players"
+            reply="$keys --era"
         fi
+    else
+        case ${COMP_WORDS[1]} in
+            "g" | "games" | "lu" | "lineup")
+                reply="--game --game-type";;
+            "r" | "results")
+                reply="--home --away --vs --month --one-run --extras --season --game-type";;
+            "u" | "schedule")
+                reply="--ics --game-type";;
+            "b" | "league-batting-stats" | "p" | "league-pitching-stats")
+                reply="--source --season --from --to --cols --chart --eras";;
+            "s" | "stats")
+                reply="--game-type --sabermetrics";;
+            "t" | "teams")
+                reply="--all --season --sort --sabermetrics";;
+            "c" | "compare")
+                read_file "players"
+                reply="$keys --era";;
+            "l" | "leaders")
+                reply="--game-type --group --league --team --position --season --qualified --all --career --single-season --min-pa --min-ip --ascending --sabermetrics";;
+        esac
     fi
 
     COMPREPLY=($(compgen -W "$reply" -- "${COMP_WORDS[COMP_CWORD]}"))
@@ -0,0 +1,112 @@
+use reqwest::blocking::get;
+use serde::Deserialize;
+use term_table::{row, Table, TableStyle};
+use term_table::row::Row;
+use term_table::table_cell::{Alignment, TableCell};
+use crate::query::{get_season, split_options, QueryError};
+
+#[derive(Deserialize)]
+struct Postseason {
+    series: Vec<Series>
+}
+
+#[derive(Deserialize)]
+struct Series {
+    games: Vec<Game>
+}
+
+#[derive(Deserialize)]
+struct Game {
+    seriesDescription: String,
+    #[serde(default)]
+    gamesInSeries: i32,
+    teams: PlayingTeams,
+    status: Status
+}
+
+#[derive(Deserialize)]
+struct PlayingTeams {
+    away: PlayingTeam,
+    home: PlayingTeam
+}
+
+#[derive(Deserialize)]
+struct PlayingTeam {
+    #[serde(default)]
+    score: i32,
+    team: Team
+}
+
+#[derive(Deserialize)]
+struct Team {
+    id: i32,
+    name: String
+}
+
+#[derive(Deserialize)]
+struct Status {
+    abstractGameState: String
+}
+
+macro_rules! postseason_url {
+    ($season:expr) => {
+        format!("https://statsapi.mlb.com/api/v1/schedule/postseason/series?sportId=1&season={}", $season)
+    };
+}
+
+fn get_series_score(series: &Series) -> Option<(&Team, i32, &Team, i32, String)> {
+    let first_game = series.games.first()?;
+    let team0 = &first_game.teams.away.team;
+    let team1 = &first_game.teams.home.team;
+
+    let mut wins = (0, 0);
+    for game in series.games.iter().filter(|game| game.status.abstractGameState == "Final") {
+        let (away, home) = (&game.teams.away, &game.teams.home);
+        let winner = if away.score > home.score { away.team.id } else { home.team.id };
+        if winner == team0.id { wins.0 += 1 } else { wins.1 += 1 }
+    }
+
+    let wins_needed = first_game.gamesInSeries / 2 + 1;
+    let state = if wins.0 == wins_needed {
+        format!("{} win", team0.name)
+    }
+    else if wins.1 == wins_needed {
+        format!("{} win", team1.name)
+    }
+    else {
+        format!("Best of {}", first_game.gamesInSeries)
+    };
+    Some((team0, wins.0, team1, wins.1, state))
+}
+
+pub(crate) fn display_bracket(query: &[String]) -> Result<(), QueryError> {
+    const COLS: usize = 6;
+
+    let (_, options) = split_options(query);
+    let season = get_season(&options)?;
+    let postseason: Postseason = get(postseason_url!(season))?.json()?;
+    if postseason.series.is_empty() {
+        return Err(QueryError::GameError(format!("No postseason series for {season}")));
+    }
+
+    let mut rounds: Vec<(&String, Table)> = Vec::new();
+    for series in &postseason.series {
+        let Some((team0, wins0, team1, wins1, state)) = get_series_score(series) else { continue };
+        let round = &series.games[0].seriesDescription;
+
+        if rounds.last().is_none_or(|(name, _)| *name != round) {
+            let mut table = Table::new();
+            table.style = TableStyle::thin();
+            table.add_row(row!(TableCell::builder(round).col_span(COLS).alignment(Alignment::Center).build()));
+            rounds.push((round, table));
+        }
+        let (_, table) = rounds.last_mut().unwrap();
+        table.add_row(row!(&team0.name, wins0, "-", wins1, &team1.name, state));
+    }
+
+    println!("\n{} Postseason\n", season);
+    for (_, table) in rounds {
+        println!("{}", table.render());
+    }
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use chrono::{DateTime, Utc};
+
+pub(crate) struct Event {
+    pub(crate) uid: String,
+    pub(crate) start: DateTime<Utc>,
+    pub(crate) end: DateTime<Utc>,
+    pub(crate) summary: String,
+    pub(crate) location: String,
+    pub(crate) description: String
+}
+
+const DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n")
+}
+
+// Content lines longer than 75 octets are folded onto continuation lines starting with a space
+fn write_line(writer: &mut impl Write, line: &str) -> std::io::Result<()> {
+    const MAX_LINE_LEN: usize = 75;
+
+    let mut line_len = 0;
+    for char in line.chars() {
+        if line_len + char.len_utf8() > MAX_LINE_LEN {
+            write!(writer, "\r\n ")?;
+            line_len = 1;
+        }
+        write!(writer, "{}", char)?;
+        line_len += char.len_utf8();
+    }
+    write!(writer, "\r\n")
+}
+
+pub(crate) fn write_calendar(path: &str, name: &str, events: &[Event]) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    let stamp = Utc::now().format(DATE_TIME_FORMAT).to_string();
+
+    write_line(&mut writer, "BEGIN:VCALENDAR")?;
+    write_line(&mut writer, "VERSION:2.0")?;
+    write_line(&mut writer, "PRODID:-//mlb//Schedule//EN")?;
+    write_line(&mut writer, "CALSCALE:GREGORIAN")?;
+    write_line(&mut writer, &format!("X-WR-CALNAME:{}", escape_text(name)))?;
+    for event in events {
+        write_line(&mut writer, "BEGIN:VEVENT")?;
+        write_line(&mut writer, &format!("UID:{}", event.uid))?;
+        write_line(&mut writer, &format!("DTSTAMP:{}", stamp))?;
+        write_line(&mut writer, &format!("DTSTART:{}", event.start.format(DATE_TIME_FORMAT)))?;
+        write_line(&mut writer, &format!("DTEND:{}", event.end.format(DATE_TIME_FORMAT)))?;
+        write_line(&mut writer, &format!("SUMMARY:{}", escape_text(&event.summary)))?;
+        write_line(&mut writer, &format!("LOCATION:{}", escape_text(&event.location)))?;
+        write_line(&mut writer, &format!("DESCRIPTION:{}", escape_text(&event.description)))?;
+        write_line(&mut writer, "END:VEVENT")?;
+    }
+    write_line(&mut writer, "END:VCALENDAR")?;
+    writer.flush()
+}
@@ -0,0 +1,86 @@
+const POINT: char = '•';
+const LINE: char = '│';
+const MARKERS: [char; 8] = ['●', '○', '■', '□', '▲', '△', '◆', '◇'];
+
+fn sample(values: &[f64], width: usize) -> Vec<f64> {
+    if values.len() <= width || width == 0 {
+        return values.to_vec();
+    }
+    (0..width).map(|i| values[i * (values.len() - 1) / (width - 1).max(1)]).collect()
+}
+
+fn get_row(value: f64, min: f64, max: f64, height: usize) -> usize {
+    let scale = if max > min { (value.clamp(min, max) - min) / (max - min) } else { 0.0 };
+    (scale * (height - 1) as f64).round() as usize
+}
+
+fn get_labels(min: f64, max: f64, height: usize, label: fn(f64) -> String) -> Vec<String> {
+    (0..height).map(|row| {
+        if row == 0 || row == height / 2 || row == height - 1 {
+            label(max - (max - min) * row as f64 / (height - 1).max(1) as f64)
+        }
+        else {
+            "".to_string()
+        }
+    }).collect()
+}
+
+fn render_grid(grid: &[Vec<char>], labels: &[String]) -> String {
+    let label_width = labels.iter().map(|label| label.chars().count()).max().unwrap_or(0);
+    let width = grid.first().map_or(0, |cols| cols.len());
+
+    let mut chart: Vec<String> = grid.iter().zip(labels.iter()).map(|(cols, label)| {
+        let axis = if label.is_empty() { '│' } else { '┤' };
+        format!("{:>label_width$} {}{}", label, axis, cols.iter().collect::<String>())
+    }).collect();
+    chart.push(format!("{} └{}", " ".repeat(label_width), "─".repeat(width)));
+    chart.join("\n")
+}
+
+fn get_plot_width(labels: &[String], width: usize) -> usize {
+    width.saturating_sub(labels.iter().map(|label| label.chars().count()).max().unwrap_or(0) + 2)
+}
+
+pub(crate) fn line_chart(values: &[f64], min: f64, max: f64, height: usize, width: usize, label: fn(f64) -> String) -> String {
+    let labels = get_labels(min, max, height, label);
+    let points = sample(values, get_plot_width(&labels, width));
+
+    let mut grid: Vec<Vec<char>> = vec![vec![' '; points.len()]; height];
+    let mut prev_row: Option<usize> = None;
+    for (col, &value) in points.iter().enumerate() {
+        let row = get_row(value, min, max, height);
+        if let Some(prev_row) = prev_row {
+            for line_row in row.min(prev_row)..=row.max(prev_row) {
+                grid[height - 1 - line_row][col] = LINE;
+            }
+        }
+        grid[height - 1 - row][col] = POINT;
+        prev_row = Some(row);
+    }
+    render_grid(&grid, &labels)
+}
+
+// Plots several series sharing one axis, each with its own marker, followed by a legend
+pub(crate) fn series_chart(series: &[(String, Vec<f64>)], height: usize, width: usize, label: fn(f64) -> String) -> String {
+    let values = series.iter().flat_map(|(_, values)| values.iter().copied());
+    let min = values.clone().fold(f64::INFINITY, f64::min);
+    let max = values.fold(f64::NEG_INFINITY, f64::max);
+    if !min.is_finite() || !max.is_finite() {
+        return "".to_string();
+    }
+
+    let labels = get_labels(min, max, height, label);
+    let plot_width = get_plot_width(&labels, width);
+    let cols = series.iter().map(|(_, values)| values.len().min(plot_width)).max().unwrap_or(0);
+
+    let mut grid: Vec<Vec<char>> = vec![vec![' '; cols]; height];
+    let mut legend: Vec<String> = Vec::with_capacity(series.len());
+    for (i, (name, values)) in series.iter().enumerate() {
+        let marker = MARKERS[i % MARKERS.len()];
+        for (col, value) in sample(values, plot_width).into_iter().enumerate() {
+            grid[height - 1 - get_row(value, min, max, height)][col] = marker;
+        }
+        legend.push(format!("{marker} {name}"));
+    }
+    format!("{}\n{}", render_grid(&grid, &labels), legend.join("   "))
+}
@@ -0,0 +1,185 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use term_table::{row, Table};
+use term_table::row::Row;
+use term_table::table_cell::TableCell;
+use crate::formula::parse_innings;
+use crate::hitting_stats::{get_year_by_year_hitting_stats, Batter};
+use crate::league_averages::{get_league_averages, get_target_averages, AverageStat, LeagueSeasonAverages};
+use crate::pitching_stats::{get_pitching_stats, Pitcher};
+use crate::query::{split_options, QueryError, REGULAR_SEASON};
+use crate::sabermetrics::{format_stat, ratio, HittingLine};
+use crate::stats::{get_player, Role, Split};
+
+// Player rate relative to the league rate, where 100 is league average
+fn relative(player: Option<f64>, league: Option<f64>) -> Option<f64> {
+    Some(100.0 * ratio(player?, league?)?)
+}
+
+// A traded player has a split for each team and one for the season as a whole, only the whole season is kept
+fn get_season_splits<T>(splits: &[Split<T>]) -> Vec<&Split<T>> {
+    let mut seasons: Vec<&Split<T>> = Vec::new();
+    for split in splits {
+        let totals = splits.iter().any(|other| other.season == split.season && other.team.is_none());
+        if !totals || split.team.is_none() {
+            seasons.push(split);
+        }
+    }
+    seasons
+}
+
+// Scales a count by how much more or less often it happened in the target era, per plate appearance or batter faced
+fn normalize(count: i32, stat: AverageStat, denominator: AverageStat, season: Option<&LeagueSeasonAverages>,
+    target: &LeagueSeasonAverages) -> Option<f64> {
+    let season_rate = ratio(season?.get(stat)?, season?.get(denominator)?)?;
+    let target_rate = ratio(target.get(stat)?, target.get(denominator)?)?;
+    Some(count as f64 * ratio(target_rate, season_rate)?)
+}
+
+fn format_count(count: Option<f64>) -> String {
+    format_stat(count, 0)
+}
+
+fn display_hitter_comparison(name: &str, splits: &[&Split<Batter>], averages: &[LeagueSeasonAverages], target: &LeagueSeasonAverages) {
+    const COLS: usize = 15;
+    const NORMALIZED: [AverageStat; 4] = [AverageStat::Hits, AverageStat::HomeRuns, AverageStat::Runs, AverageStat::RunsBattedIn];
+
+    let mut table = Table::new();
+    table.add_row(row!(TableCell::builder(format!("{name}, Normalized To {}", target.year)).col_span(COLS).build()));
+    table.add_row(row!("Year", "PA", "OPS", "OPS+", "K%", "K%+", "BB%", "BB%+", "H", "HR", "R", "RBI",
+        "Adj H", "Adj HR", "Adj R / RBI"));
+
+    let mut totals = [0.0; NORMALIZED.len()];
+    for split in splits {
+        let stat = &split.stat;
+        let season = averages.iter().find(|averages| averages.year == split.season);
+        let line = HittingLine::from(stat);
+        let plate_appearances = stat.plateAppearances as f64;
+        let league_rate = |average: AverageStat| ratio(season?.get(average)?, season?.get(AverageStat::PlateAppearances)?);
+
+        let ops_plus = season.and_then(|season| Some(100.0 * (ratio(line.obp()?, season.get(AverageStat::OnBasePct)?)?
+            + ratio(line.slg()?, season.get(AverageStat::SluggingPct)?)? - 1.0)));
+        let strikeout_pct = ratio(stat.strikeOuts as f64, plate_appearances);
+        let walk_pct = ratio(stat.baseOnBalls as f64, plate_appearances);
+        let counts = [stat.hits, stat.homeRuns, stat.runs, stat.rbi];
+        let normalized: Vec<Option<f64>> = counts.iter().zip(NORMALIZED.iter())
+            .map(|(count, average)| normalize(*count, *average, AverageStat::PlateAppearances, season, target)).collect();
+        for (total, value) in totals.iter_mut().zip(normalized.iter()) {
+            *total += value.unwrap_or(0.0);
+        }
+
+        table.add_row(row!(
+            &split.season, stat.plateAppearances, &stat.ops, format_stat(ops_plus, 0),
+            format_stat(strikeout_pct.map(|pct| 100.0 * pct), 1),
+            format_stat(relative(strikeout_pct, league_rate(AverageStat::Strikeouts)), 0),
+            format_stat(walk_pct.map(|pct| 100.0 * pct), 1), format_stat(relative(walk_pct, league_rate(AverageStat::Walks)), 0),
+            stat.hits, stat.homeRuns, stat.runs, stat.rbi,
+            format_count(normalized[0]), format_count(normalized[1]),
+            format!("{} / {}", format_count(normalized[2]), format_count(normalized[3]))
+        ));
+    }
+
+    let total = |count: fn(&Batter) -> i32| splits.iter().map(|split| count(&split.stat)).sum::<i32>();
+    let adjusted = |i: usize| format_count(Some(totals[i]));
+    table.add_row(row!("Total", total(|stat| stat.plateAppearances), "", "", "", "", "", "",
+        total(|stat| stat.hits), total(|stat| stat.homeRuns), total(|stat| stat.runs), total(|stat| stat.rbi),
+        adjusted(0), adjusted(1), format!("{} / {}", adjusted(2), adjusted(3))));
+    println!("{}", table.render());
+}
+
+fn display_pitcher_comparison(name: &str, splits: &[&Split<Pitcher>], averages: &[LeagueSeasonAverages], target: &LeagueSeasonAverages) {
+    const COLS: usize = 15;
+    const NORMALIZED: [AverageStat; 3] = [AverageStat::Strikeouts, AverageStat::Walks, AverageStat::HomeRuns];
+
+    let mut table = Table::new();
+    table.add_row(row!(TableCell::builder(format!("{name}, Normalized To {}", target.year)).col_span(COLS).build()));
+    table.add_row(row!("Year", "IP", "ERA", "ERA+", "Adj ERA", "K%", "K%+", "BB%", "BB%+", "SO", "BB", "HR",
+        "Adj SO", "Adj BB", "Adj HR"));
+
+    let mut totals = [0.0; NORMALIZED.len()];
+    for split in splits {
+        let stat = &split.stat;
+        let season = averages.iter().find(|averages| averages.year == split.season);
+        let batters_faced = stat.battersFaced as f64;
+        let league_rate = |average: AverageStat| ratio(season?.get(average)?, season?.get(AverageStat::BattersFaced)?);
+
+        let era = parse_innings(&stat.inningsPitched).and_then(|innings| ratio(9.0 * stat.earnedRuns as f64, innings));
+        let era_plus = relative(season.and_then(|season| season.get(AverageStat::Era)), era);
+        let adjusted_era = era.and_then(|era| Some(era * ratio(target.get(AverageStat::Era)?, season?.get(AverageStat::Era)?)?));
+        let strikeout_pct = ratio(stat.strikeOuts as f64, batters_faced);
+        let walk_pct = ratio(stat.baseOnBalls as f64, batters_faced);
+        let counts = [stat.strikeOuts, stat.baseOnBalls, stat.homeRuns];
+        let normalized: Vec<Option<f64>> = counts.iter().zip(NORMALIZED.iter())
+            .map(|(count, average)| normalize(*count, *average, AverageStat::BattersFaced, season, target)).collect();
+        for (total, value) in totals.iter_mut().zip(normalized.iter()) {
+            *total += value.unwrap_or(0.0);
+        }
+
+        table.add_row(row!(
+            &split.season, &stat.inningsPitched, &stat.era, format_stat(era_plus, 0), format_stat(adjusted_era, 2),
+            format_stat(strikeout_pct.map(|pct| 100.0 * pct), 1),
+            format_stat(relative(strikeout_pct, league_rate(AverageStat::Strikeouts)), 0),
+            format_stat(walk_pct.map(|pct| 100.0 * pct), 1), format_stat(relative(walk_pct, league_rate(AverageStat::Walks)), 0),
+            stat.strikeOuts, stat.baseOnBalls, stat.homeRuns,
+            format_count(normalized[0]), format_count(normalized[1]), format_count(normalized[2])
+        ));
+    }
+
+    // Innings are totalled as outs and written back in the usual "200.1" notation
+    let outs = (3.0 * splits.iter().filter_map(|split| parse_innings(&split.stat.inningsPitched)).sum::<f64>()).round() as i32;
+    let total = |count: fn(&Pitcher) -> i32| splits.iter().map(|split| count(&split.stat)).sum::<i32>();
+    let adjusted = |i: usize| format_count(Some(totals[i]));
+    table.add_row(row!("Total", format!("{}.{}", outs / 3, outs % 3), "", "", "", "", "", "", "",
+        total(|stat| stat.strikeOuts), total(|stat| stat.baseOnBalls), total(|stat| stat.homeRuns),
+        adjusted(0), adjusted(1), adjusted(2)));
+    println!("{}", table.render());
+}
+
+pub(crate) fn compare_query(query: &[String]) -> Result<(), QueryError> {
+    const PLAYERS_INDEX: usize = 2;
+    const ERA_OPTION: &str = "era";
+
+    let (query, options) = split_options(query);
+    if query.len() <= PLAYERS_INDEX {
+        return Err(QueryError::QueryTooShort("No Players Provided".to_string()));
+    }
+
+    let mut league_averages: HashMap<bool, Vec<LeagueSeasonAverages>> = HashMap::new();
+    for player in &query[PLAYERS_INDEX..] {
+        let (id, role) = get_player(player)?;
+        // Two-way players are compared as both a hitter and a pitcher
+        let groups: &[bool] = match role {
+            Role::Hitter => &[true],
+            Role::Pitcher => &[false],
+            Role::TwoWay => &[true, false]
+        };
+
+        for &is_batting in groups {
+            if let Entry::Vacant(entry) = league_averages.entry(is_batting) {
+                entry.insert(get_league_averages(is_batting)?);
+            }
+            let averages = &league_averages[&is_batting];
+
+            // Without a target era counts are normalized to the most recent season
+            let target = match options.get(ERA_OPTION) {
+                Some(era) => get_target_averages(averages, era, is_batting)
+                    .ok_or(QueryError::OptionError(ERA_OPTION.to_string(), era.clone()))?,
+                None => averages.first().cloned().ok_or(QueryError::EntryError(player.clone()))?
+            };
+
+            println!();
+            if is_batting {
+                let splits = get_year_by_year_hitting_stats(id, REGULAR_SEASON)?;
+                let name = splits.first().map_or(player.as_str(), |split| split.player.fullName.as_str());
+                display_hitter_comparison(name, &get_season_splits(&splits), averages, &target);
+            }
+            else {
+                let stats = get_pitching_stats(id, "yearByYear", REGULAR_SEASON)?;
+                let Some(stat) = stats.stats.first() else { continue };
+                let name = stat.splits.first().map_or(player.as_str(), |split| split.player.fullName.as_str());
+                display_pitcher_comparison(name, &get_season_splits(&stat.splits), averages, &target);
+            }
+        }
+    }
+    Ok(())
+}
@@ -36,7 +36,7 @@ pub(crate) struct Team {
     #[serde(default)]
     pub(crate) name: String,
     #[serde(default)]
-    abbreviation: String
+    pub(crate) abbreviation: String
 }
 
 const START_SEASON: i32 = 1876;
@@ -76,25 +76,31 @@ fn get_players(all_time: bool) -> Result<HashMap<String, HashMap<i32, String>>,
     Ok(baseball_players)
 }
 
+// Players are stored as hitters (0), pitchers (1) or two-way players (2), see stats::get_player
+fn get_role(position: &str) -> u8 {
+    match position {
+        "P" => 1,
+        "TWP" => 2,
+        _ => 0
+    }
+}
+
 pub(crate) fn update_players(all_time: bool) -> Result<(), QueryError> {
     let baseball_players = get_players(all_time)?;
-    let mut sorted_players: Vec<(String, bool, &i32)> = Vec::with_capacity(baseball_players.len());
+    let mut sorted_players: Vec<(String, u8, &i32)> = Vec::with_capacity(baseball_players.len());
 
     let mut max_len = 0;
     for (name, players) in &baseball_players {
-        let mut i = 0;
         let is_multiple_players = players.len() > 1;
-        for (player_id, position) in players {
-            let distinct_name;
-            if is_multiple_players {
-                distinct_name = format!("{}-{}-{}", name.clone(), i, position);
+        for (i, (player_id, position)) in players.iter().enumerate() {
+            let distinct_name = if is_multiple_players {
+                format!("{}-{}-{}", name.clone(), i, position)
             }
             else {
-                distinct_name = name.clone();
-            }
+                name.clone()
+            };
             max_len = max(max_len, distinct_name.len());
-            sorted_players.push((distinct_name, position == "P", player_id));
-            i += 1;
+            sorted_players.push((distinct_name, get_role(position), player_id));
         }
     }
     sorted_players.sort();
@@ -105,8 +111,8 @@ pub(crate) fn update_players(all_time: bool) -> Result<(), QueryError> {
     let player_file = File::create(file_path!("auto_complete/players.txt"))?;
     let mut player_writer = LineWriter::new(player_file);
 
-    for (name, is_pitcher, id) in sorted_players {
-        writeln!(player_id_writer, "{} {}{} {}", name, " ".repeat(max_len - name.len()), is_pitcher as u8, id)?;
+    for (name, role, id) in sorted_players {
+        writeln!(player_id_writer, "{} {}{} {}", name, " ".repeat(max_len - name.len()), role, id)?;
         writeln!(player_writer, "{}", name)?;
     }
     player_id_writer.flush()?;
@@ -143,7 +149,7 @@ pub(crate) fn update_teams() -> Result<(), QueryError> {
     Ok(())
 }
 
-pub(crate) fn update_database(query: &Vec<String>) -> Result<(), QueryError>  {
+pub(crate) fn update_database(query: &[String]) -> Result<(), QueryError>  {
     const DATASET_INDEX: usize = 2;
 
     let data_set = get_query_param!(query, DATASET_INDEX, empty!());
@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use phf_macros::phf_map;
+use serde_json::{Map, Value};
+use crate::query::QueryError;
+
+// Short names usable in formulas, expanded to the StatsAPI fields (or expressions over them) they stand for
+const STAT_ALIASES: phf::Map<&'static str, &'static str> = phf_map! {
+    "g" => "gamesPlayed",
+    "pa" => "plateAppearances",
+    "ab" => "atBats",
+    "r" => "runs",
+    "h" => "hits",
+    "hr" => "homeRuns",
+    "bb" => "baseOnBalls",
+    "ibb" => "intentionalWalks",
+    "so" => "strikeOuts",
+    "k" => "strikeOuts",
+    "hbp" => "hitByPitch",
+    "sb" => "stolenBases",
+    "cs" => "caughtStealing",
+    "sf" => "sacFlies",
+    "sh" => "sacBunts",
+    "tb" => "totalBases",
+    "gdp" => "groundIntoDoublePlay",
+    "go" => "groundOuts",
+    "ao" => "airOuts",
+    "fb" => "airOuts + homeRuns",
+    "ip" => "inningsPitched",
+    "er" => "earnedRuns",
+    "w" => "wins",
+    "l" => "losses",
+    "sv" => "saves",
+    "bf" => "battersFaced",
+};
+
+pub(crate) enum Formula {
+    Number(f64),
+    Stat(String),
+    Negate(Box<Formula>),
+    Binary(char, Box<Formula>, Box<Formula>)
+}
+
+#[derive(Clone)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Operator(char)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    expand_aliases: bool
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, QueryError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        match chars[i] {
+            char if char.is_whitespace() => i += 1,
+            '+' | '-' | '*' | '/' | '(' | ')' => {
+                tokens.push(Token::Operator(chars[i]));
+                i += 1;
+            },
+            char if char.is_ascii_digit() || char == '.' => {
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let number: String = chars[start..i].iter().collect();
+                tokens.push(Token::Number(number.parse::<f64>().map_err(|_| QueryError::FormulaError(number))?));
+            },
+            char if char.is_ascii_alphabetic() => {
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(Token::Name(chars[start..i].iter().collect::<String>().to_ascii_lowercase()));
+            },
+            char => return Err(QueryError::FormulaError(char.to_string()))
+        }
+    }
+    Ok(tokens)
+}
+
+// expression := term (('+' | '-') term)*, term := factor (('*' | '/') factor)*, factor := '-' factor | number | name | '(' expression ')'
+impl Parser {
+    fn peek_operator(&self) -> Option<char> {
+        match self.tokens.get(self.position) {
+            Some(Token::Operator(operator)) => Some(*operator),
+            _ => None
+        }
+    }
+
+    fn expression(&mut self) -> Result<Formula, QueryError> {
+        let mut formula = self.term()?;
+        while let Some(operator @ ('+' | '-')) = self.peek_operator() {
+            self.position += 1;
+            formula = Formula::Binary(operator, Box::new(formula), Box::new(self.term()?));
+        }
+        Ok(formula)
+    }
+
+    fn term(&mut self) -> Result<Formula, QueryError> {
+        let mut formula = self.factor()?;
+        while let Some(operator @ ('*' | '/')) = self.peek_operator() {
+            self.position += 1;
+            formula = Formula::Binary(operator, Box::new(formula), Box::new(self.factor()?));
+        }
+        Ok(formula)
+    }
+
+    fn factor(&mut self) -> Result<Formula, QueryError> {
+        let token = self.tokens.get(self.position).cloned().ok_or(QueryError::FormulaError("unexpected end".to_string()))?;
+        self.position += 1;
+        match token {
+            Token::Number(number) => Ok(Formula::Number(number)),
+            Token::Name(name) => match STAT_ALIASES.get(name.as_str()) {
+                Some(alias) if self.expand_aliases => parse(alias, false),
+                _ => Ok(Formula::Stat(name))
+            },
+            Token::Operator('-') => Ok(Formula::Negate(Box::new(self.factor()?))),
+            Token::Operator('(') => {
+                let formula = self.expression()?;
+                match self.peek_operator() {
+                    Some(')') => {
+                        self.position += 1;
+                        Ok(formula)
+                    },
+                    _ => Err(QueryError::FormulaError("missing )".to_string()))
+                }
+            },
+            Token::Operator(operator) => Err(QueryError::FormulaError(operator.to_string()))
+        }
+    }
+}
+
+fn parse(text: &str, expand_aliases: bool) -> Result<Formula, QueryError> {
+    let mut parser = Parser { tokens: tokenize(text)?, position: 0, expand_aliases };
+    let formula = parser.expression()?;
+    if parser.position < parser.tokens.len() {
+        return Err(QueryError::FormulaError(text.to_string()));
+    }
+    Ok(formula)
+}
+
+pub(crate) fn parse_formula(text: &str) -> Result<Formula, QueryError> {
+    parse(text, true)
+}
+
+impl Formula {
+    // None when a stat is missing or the formula divides by zero, so the player is left off the board
+    pub(crate) fn evaluate(&self, stats: &HashMap<String, f64>) -> Option<f64> {
+        match self {
+            Formula::Number(number) => Some(*number),
+            Formula::Stat(name) => stats.get(&name.to_ascii_lowercase()).copied(),
+            Formula::Negate(formula) => formula.evaluate(stats).map(|value| -value),
+            Formula::Binary(operator, left, right) => {
+                let (left, right) = (left.evaluate(stats)?, right.evaluate(stats)?);
+                match operator {
+                    '+' => Some(left + right),
+                    '-' => Some(left - right),
+                    '*' => Some(left * right),
+                    _ => if right == 0.0 { None } else { Some(left / right) }
+                }
+            }
+        }
+    }
+
+    pub(crate) fn get_stat_names(&self) -> Vec<String> {
+        match self {
+            Formula::Number(_) => Vec::new(),
+            Formula::Stat(name) => vec![name.to_ascii_lowercase()],
+            Formula::Negate(formula) => formula.get_stat_names(),
+            Formula::Binary(_, left, right) => [left.get_stat_names(), right.get_stat_names()].concat()
+        }
+    }
+}
+
+// Innings are reported as whole innings plus outs after the decimal, e.g. "6.2" is six and two thirds
+pub(crate) fn parse_innings(innings: &str) -> Option<f64> {
+    let (whole, outs) = innings.split_once('.').unwrap_or((innings, "0"));
+    Some(whole.parse::<f64>().ok()? + outs.parse::<f64>().ok()? / 3.0)
+}
+
+// Numeric fields keyed by lower case name, including rate stats the API sends as strings like ".312"
+pub(crate) fn get_stat_values(stat: &Map<String, Value>) -> HashMap<String, f64> {
+    const INNINGS_FIELD: &str = "inningsPitched";
+
+    stat.iter().filter_map(|(name, value)| {
+        let value = match value {
+            Value::Number(number) => number.as_f64(),
+            Value::String(text) if name == INNINGS_FIELD => parse_innings(text),
+            Value::String(text) => text.parse::<f64>().ok(),
+            _ => None
+        }?;
+        Some((name.to_ascii_lowercase(), value))
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(values: &[(&str, f64)]) -> HashMap<String, f64> {
+        values.iter().map(|(name, value)| (name.to_ascii_lowercase(), *value)).collect()
+    }
+
+    fn evaluate(text: &str, values: &[(&str, f64)]) -> Option<f64> {
+        parse_formula(text).ok()?.evaluate(&stats(values))
+    }
+
+    fn assert_close(value: Option<f64>, expected: f64) {
+        assert!(value.is_some_and(|value| (value - expected).abs() < 1e-9), "{value:?} != {expected}");
+    }
+
+    #[test]
+    fn multiplication_binds_tighter_than_subtraction() {
+        let values = [("baseOnBalls", 10.0), ("intentionalWalks", 2.0), ("plateAppearances", 100.0)];
+        assert_close(evaluate("(BB - IBB) / PA", &values), 0.08);
+        assert_close(evaluate("BB - IBB / PA", &values), 9.98);
+    }
+
+    #[test]
+    fn aliases_expand_as_one_grouped_factor() {
+        let values = [("homeRuns", 10.0), ("airOuts", 30.0)];
+        assert_close(evaluate("hr/fb", &values), 0.25);
+        assert_eq!(parse_formula("hr/fb").unwrap().get_stat_names(), ["homeruns", "airouts", "homeruns"]);
+    }
+
+    #[test]
+    fn unary_minus_negates_a_stat() {
+        assert_close(evaluate("-era", &[("era", 3.5)]), -3.5);
+        assert_close(evaluate("2 - -era", &[("era", 3.5)]), 5.5);
+    }
+
+    #[test]
+    fn malformed_formulas_are_errors() {
+        assert!(matches!(parse_formula("1.2.3"), Err(QueryError::FormulaError(_))));
+        assert!(matches!(parse_formula("(bb + hbp"), Err(QueryError::FormulaError(_))));
+        assert!(matches!(parse_formula("bb hbp"), Err(QueryError::FormulaError(_))));
+    }
+
+    #[test]
+    fn division_by_zero_and_missing_stats_have_no_value() {
+        assert_eq!(evaluate("hr / pa", &[("homeRuns", 3.0), ("plateAppearances", 0.0)]), None);
+        assert_eq!(evaluate("hr / pa", &[("homeRuns", 3.0)]), None);
+    }
+
+    #[test]
+    fn innings_count_outs_as_thirds() {
+        assert_close(parse_innings("6.2"), 6.0 + 2.0 / 3.0);
+        assert_close(parse_innings("200"), 200.0);
+    }
+}
@@ -1,56 +1,109 @@
 use figlet_rs::FIGfont;
 use std::collections::HashMap;
 use std::{mem};
-use chrono::{Datelike, Utc};
+use std::io::{stdin, stdout, IsTerminal, Write};
+use chrono::{Datelike, Duration, Utc};
 use serde::Deserialize;
 use reqwest::blocking::get;
 use term_table::{row, rows, Table};
 use term_table::row::Row;
 use term_table::table_cell::TableCell;
 use term_table::TableStyle;
-use crate::hitting_stats::{Batter};
-use crate::pitching_stats::{Pitcher};
+use crate::hitting_stats::{get_matchup_stats, Batter};
+use crate::sabermetrics::get_pythagorean_pct;
+use crate::pitching_stats::{format_pitcher_summary, get_pitcher_summary, Pitcher};
 use crate::{database, stats};
-use crate::query::{empty, get_query_param, QueryError};
+use crate::calendar::{write_calendar, Event};
+use crate::chart::line_chart;
+use crate::query::{empty, get_query_param, get_schedule_game_type, get_season, split_options, terminal_width, QueryError, POSTSEASON, REGULAR_SEASON};
 use crate::teams::get_team;
 
 #[derive(Deserialize)]
-struct Schedule {
-    dates: Vec<Date>
+pub(crate) struct Schedule {
+    pub(crate) dates: Vec<Date>
 }
 
 #[derive(Deserialize)]
-struct Date {
-    games: Vec<Game>
+pub(crate) struct Date {
+    pub(crate) games: Vec<Game>
 }
 
 #[derive(Deserialize)]
-struct Game {
+pub(crate) struct Game {
     gamePk: i32,
-    gameDate: String,
+    pub(crate) gameDate: String,
     officialDate: String,
-    teams: PlayingTeams,
-    status: Status
+    pub(crate) teams: PlayingTeams,
+    pub(crate) status: Status,
+    pub(crate) linescore: Option<LineScore>,
+    pub(crate) decisions: Option<Decisions>,
+    #[serde(default)]
+    gameNumber: i32,
+    #[serde(default)]
+    resumeGameDate: String,
+    #[serde(default)]
+    resumedFromDate: String,
+    venue: Option<Venue>,
+    #[serde(default)]
+    broadcasts: Vec<Broadcast>
+}
+
+#[derive(Deserialize)]
+struct Venue {
+    name: String
 }
 
 #[derive(Deserialize)]
-struct PlayingTeams {
-    away: PlayingTeam,
-    home: PlayingTeam
+struct Broadcast {
+    name: String,
+    #[serde(default, rename = "type")]
+    broadcastType: String
+}
+
+#[derive(Deserialize)]
+pub(crate) struct PlayingTeams {
+    pub(crate) away: PlayingTeam,
+    pub(crate) home: PlayingTeam
 }
 
 #[derive(Deserialize)]
-struct PlayingTeam {
+pub(crate) struct PlayingTeam {
     leagueRecord: Record,
     #[serde(default)]
     score: i32,
-    team: database::Team
+    pub(crate) team: database::Team,
+    pub(crate) probablePitcher: Option<ProbablePitcher>
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ProbablePitcher {
+    #[serde(flatten)]
+    pub(crate) person: stats::Player,
+    // Only hydrated on the schedule, kept loose so a malformed stat line doesn't fail the whole schedule
+    #[serde(default)]
+    stats: Vec<serde_json::Value>
 }
 
 #[derive(Deserialize)]
-struct Status {
-    abstractGameState: String,
-    detailedState: String
+pub(crate) struct Decisions {
+    pub(crate) winner: Option<stats::Player>,
+    pub(crate) loser: Option<stats::Player>,
+    pub(crate) save: Option<stats::Player>
+}
+
+struct ResultFilters {
+    home: bool,
+    away: bool,
+    vs: Option<i32>,
+    month: Option<u32>,
+    one_run: bool,
+    extras: bool
+}
+
+#[derive(Deserialize)]
+pub(crate) struct Status {
+    pub(crate) abstractGameState: String,
+    pub(crate) detailedState: String
 }
 
 #[derive(Deserialize)]
@@ -62,7 +115,28 @@ struct Feed {
 #[derive(Deserialize)]
 struct Data {
     datetime: DateTime,
-    status: Status
+    status: Status,
+    #[serde(default)]
+    probablePitchers: ProbablePitchers,
+    #[serde(default)]
+    players: HashMap<String, FeedPlayer>
+}
+
+#[derive(Deserialize)]
+struct FeedPlayer {
+    #[serde(default)]
+    batSide: Hand
+}
+
+#[derive(Deserialize, Default)]
+struct Hand {
+    code: String
+}
+
+#[derive(Deserialize, Default)]
+struct ProbablePitchers {
+    away: Option<stats::Player>,
+    home: Option<stats::Player>
 }
 
 #[derive(Deserialize)]
@@ -97,41 +171,67 @@ struct Movement {
 }
 
 #[derive(Deserialize)]
-struct LineScore {
-    innings: Vec<Inning>,
-    teams: TeamScores,
+pub(crate) struct LineScore {
+    #[serde(default)]
+    pub(crate) innings: Vec<Inning>,
+    #[serde(default = "default_scheduled_innings")]
+    pub(crate) scheduledInnings: i32,
+    #[serde(default)]
+    pub(crate) teams: TeamScores,
     #[serde(default)]
-    inningState: String,
+    pub(crate) inningState: String,
     #[serde(default)]
-    currentInningOrdinal: String,
+    pub(crate) currentInningOrdinal: String,
     #[serde(default)]
-    outs: i32,
+    pub(crate) outs: i32,
     #[serde(default)]
     balls: i32,
     #[serde(default)]
-    strikes: i32
+    strikes: i32,
+    pub(crate) offense: Option<Offense>,
+    pub(crate) defense: Option<Defense>
+}
+
+impl Default for LineScore {
+    fn default() -> Self {
+        LineScore {
+            innings: Vec::new(), scheduledInnings: default_scheduled_innings(), teams: TeamScores::default(),
+            inningState: "".to_string(), currentInningOrdinal: "".to_string(), outs: 0, balls: 0, strikes: 0,
+            offense: None, defense: None
+        }
+    }
 }
 
 #[derive(Deserialize)]
-struct Inning {
-    num: i32,
-    home: Score,
-    away: Score
+pub(crate) struct Offense {
+    pub(crate) batter: Option<stats::Player>
 }
 
 #[derive(Deserialize)]
-struct TeamScores {
-    away: Score,
-    home: Score
+pub(crate) struct Defense {
+    pub(crate) pitcher: Option<stats::Player>
 }
 
 #[derive(Deserialize)]
-struct Score {
-    runs: Option<i32>,
+pub(crate) struct Inning {
+    pub(crate) num: i32,
+    pub(crate) home: Score,
+    pub(crate) away: Score
+}
+
+#[derive(Deserialize, Default)]
+pub(crate) struct TeamScores {
+    pub(crate) away: Score,
+    pub(crate) home: Score
+}
+
+#[derive(Deserialize, Default)]
+pub(crate) struct Score {
+    pub(crate) runs: Option<i32>,
     #[serde(default)]
-    hits: i32,
+    pub(crate) hits: i32,
     #[serde(default)]
-    errors: i32,
+    pub(crate) errors: i32,
     #[serde(default)]
     leftOnBase: i32
 }
@@ -160,7 +260,16 @@ struct Player {
     stats: Stats,
     seasonStats: Stats,
     #[serde(default)]
-    battingOrder: String
+    battingOrder: String,
+    #[serde(default)]
+    position: Position,
+    #[serde(default)]
+    allPositions: Vec<Position>
+}
+
+#[derive(Deserialize, Default)]
+struct Position {
+    abbreviation: String
 }
 
 #[derive(Deserialize)]
@@ -179,7 +288,31 @@ struct Record {
 #[derive(Deserialize)]
 struct WinProbability {
     homeTeamWinProbability: f64,
-    awayTeamWinProbability: f64
+    awayTeamWinProbability: f64,
+    #[serde(default)]
+    homeTeamWinProbabilityAdded: f64,
+    result: PlayResult,
+    about: PlayAbout,
+    matchup: Matchup
+}
+
+#[derive(Deserialize)]
+struct PlayResult {
+    #[serde(default)]
+    description: String
+}
+
+#[derive(Deserialize)]
+struct PlayAbout {
+    halfInning: String,
+    inning: i32,
+    isTopInning: bool
+}
+
+#[derive(Deserialize)]
+struct Matchup {
+    batter: stats::Player,
+    pitcher: stats::Player
 }
 
 #[derive(Deserialize)]
@@ -190,13 +323,19 @@ struct Stats {
     pitching: Option<Pitcher>
 }
 
+fn default_scheduled_innings() -> i32 {
+    9
+}
+
 fn deserialize_stats<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
 where T: serde::Deserialize<'de>, D: serde::Deserializer<'de> {
     Ok(Option::<T>::deserialize(deserializer).unwrap_or(None))
 }
 
 macro_rules! games_url {
-    ($queries:expr) => { format!("https://statsapi.mlb.com/api/v1/schedule/games/?sportId=1{}", $queries) };
+    ($queries:expr, $game_type:expr) => {
+        format!("https://statsapi.mlb.com/api/v1/schedule/games/?sportId=1{}{}", schedule_game_types($game_type), $queries)
+    };
 }
 
 macro_rules! box_score_url {
@@ -212,8 +351,15 @@ macro_rules! game_feed_url {
 }
 
 macro_rules! season_games_url {
-    ($team_id:expr, $season:expr) => {
-        format!("https://statsapi.mlb.com/api/v1/schedule?sportId=1&teamId={}&season={}", $team_id, $season)
+    ($team_id:expr, $season:expr, $game_type:expr) => {
+        format!("https://statsapi.mlb.com/api/v1/schedule?sportId=1&teamId={}&season={}{}&hydrate=probablePitcher(stats(group=[pitching],type=[season])),linescore,venue,broadcasts(all)",
+            $team_id, $season, schedule_game_types($game_type))
+    };
+}
+
+macro_rules! league_schedule_url {
+    ($season:expr) => {
+        format!("https://statsapi.mlb.com/api/v1/schedule?sportId=1&season={}&gameType=R", $season)
     };
 }
 
@@ -253,16 +399,33 @@ macro_rules! pitching_row {
 }
 
 macro_rules! display_stat_table {
-    ($team_stats:expr, $players:expr, $stat_type:ident, $header:ident, $row_generator:ident) => {{
+    ($team_stats:expr, $players:expr, $stat_type:ident, $header:ident, $row_generator:ident, $wpa:expr) => {{
         let mut stat_table = Table::new();
-        stat_table.add_row($header!());
-        $players.iter().for_each(|&player|
-            stat_table.add_row($row_generator!(
-            &player.person.fullName,
-            player.stats.$stat_type.as_ref().unwrap(),
-            player.seasonStats.$stat_type.as_ref().unwrap()))
-        );
-        stat_table.add_row($row_generator!("Team", $team_stats, $team_stats));
+        let wpa: Option<&HashMap<i32, f64>> = $wpa;
+        let mut header = $header!();
+        if wpa.is_some() {
+            header.cells.push(TableCell::new("WPA"));
+        }
+        stat_table.add_row(header);
+
+        let mut team_wpa = 0.0;
+        $players.iter().for_each(|&player| {
+            let mut row = $row_generator!(
+                &player.person.fullName,
+                player.stats.$stat_type.as_ref().unwrap(),
+                player.seasonStats.$stat_type.as_ref().unwrap());
+            if let Some(wpa) = wpa {
+                let player_wpa = wpa.get(&player.person.id).copied().unwrap_or(0.0);
+                team_wpa += player_wpa;
+                row.cells.push(TableCell::new(format_wpa(player_wpa)));
+            }
+            stat_table.add_row(row);
+        });
+        let mut team_row = $row_generator!("Team", $team_stats, $team_stats);
+        if wpa.is_some() {
+            team_row.cells.push(TableCell::new(format_wpa(team_wpa)));
+        }
+        stat_table.add_row(team_row);
         println!("{}", stat_table.render());
     }};
 }
@@ -312,7 +475,19 @@ macro_rules! upcoming_game {
     }};
 }
 
-fn get_eastern_standard_time(date_time: &String) -> String {
+// The game type filter for a schedule request, empty when no game type was asked for
+pub(crate) fn schedule_game_types(game_type: Option<&str>) -> String {
+    // The schedule endpoint only knows the individual postseason rounds
+    const POSTSEASON_ROUNDS: &str = "F,D,L,W";
+
+    match game_type {
+        Some(POSTSEASON) => format!("&gameType={POSTSEASON_ROUNDS}"),
+        Some(game_type) => format!("&gameType={game_type}"),
+        None => "".to_string()
+    }
+}
+
+pub(crate) fn get_eastern_standard_time(date_time: &str) -> String {
     const EST_OFFSET: i32 = 20;
     const HOURS: i32 = 12;
 
@@ -327,19 +502,21 @@ fn get_eastern_standard_time(date_time: &String) -> String {
     format!("{}{}{}", hour_12, minutes, time_of_day)
 }
 
+fn is_suspended(status: &Status) -> bool {
+    status.detailedState.starts_with("Suspended")
+}
+
 fn get_game_state(feed: &Feed) -> Vec<String> {
+    if is_suspended(&feed.gameData.status) {
+        return vec![feed.gameData.status.detailedState.clone()];
+    }
     match feed.gameData.status.abstractGameState.as_str() {
         "Final" => vec!["Final".to_string()],
         "Live" => {
             let line_score = &feed.liveData.linescore;
             let bases: Vec<String> = feed.liveData.plays.currentPlay.as_ref()
                 .unwrap_or(&Play { runners: Vec::new() }).runners
-                .iter().filter_map(|runner| {
-                match &runner.movement.end {
-                    Some(bases) => Some(bases.clone()),
-                    None => None
-                }
-            }).collect();
+                .iter().filter_map(|runner| runner.movement.end.clone()).collect();
             vec![
                 format!("{} {}", line_score.inningState, line_score.currentInningOrdinal),
                 format!("{}-{}", line_score.balls, line_score.strikes),
@@ -351,12 +528,68 @@ fn get_game_state(feed: &Feed) -> Vec<String> {
     }
 }
 
-fn display_team_stats(team: &Team, hitters: &Vec<&Player>, pitchers: &Vec<&Player>) {
+fn format_wpa(wpa: f64) -> String {
+    // WPA is reported in percentage points but conventionally shown in wins
+    format!("{:+.3}", wpa / 100.0)
+}
+
+fn display_team_stats(team: &Team, hitters: &[&Player], pitchers: &[&Player], wpa: Option<&PlayerWpa>) {
     println!("{} Stats\n\nBatting", &team.team.name);
-    display_stat_table!(team.teamStats.batting.as_ref().unwrap(), hitters, batting, hitting_header, hitting_row);
+    display_stat_table!(team.teamStats.batting.as_ref().unwrap(), hitters, batting, hitting_header, hitting_row,
+        wpa.map(|wpa| &wpa.batting));
 
     println!("Pitching");
-    display_stat_table!(team.teamStats.pitching.as_ref().unwrap(), pitchers, pitching, pitching_header, pitching_row);
+    display_stat_table!(team.teamStats.pitching.as_ref().unwrap(), pitchers, pitching, pitching_header, pitching_row,
+        wpa.map(|wpa| &wpa.pitching));
+}
+
+fn get_lineup(team: &Team) -> Vec<&Player> {
+    let mut lineup: Vec<&Player> = team.players.values().filter(|player| !player.battingOrder.is_empty()).collect();
+    lineup.sort_by(|player0, player1| player0.battingOrder.cmp(&player1.battingOrder));
+    lineup
+}
+
+fn display_lineup(feed: &Feed, team: &Team) {
+    const STARTER_SUFFIX: &str = "00";
+
+    let lineup = get_lineup(team);
+    if lineup.is_empty() {
+        println!("{} lineup not yet posted\n", team.team.name);
+        return;
+    }
+
+    let mut table = Table::new();
+    table.add_row(row!("#", "Player", "Pos", "Bats", "AVG/OBP/SLG", "Note"));
+    for player in lineup {
+        let is_starter = player.battingOrder.ends_with(STARTER_SUFFIX);
+        let order = if is_starter { player.battingOrder[..1].to_string() } else { "".to_string() };
+        let name = if is_starter { player.person.fullName.clone() } else { format!("  {}", player.person.fullName) };
+        let bats = feed.gameData.players.get(&format!("ID{}", player.person.id))
+            .map_or("", |feed_player| feed_player.batSide.code.as_str());
+        let slash_line = player.seasonStats.batting.as_ref()
+            .map_or("-".to_string(), |stats| format!("{}/{}/{}", stats.avg, stats.obp, stats.slg));
+
+        let mut notes: Vec<String> = Vec::new();
+        if !is_starter {
+            notes.push("Substitute".to_string());
+        }
+        if player.allPositions.len() > 1 {
+            let positions: Vec<&str> = player.allPositions.iter().map(|position| position.abbreviation.as_str()).collect();
+            notes.push(format!("Moved {}", positions.join("-")));
+        }
+        table.add_row(row!(order, name, &player.position.abbreviation, bats, slash_line, notes.join(", ")));
+    }
+    println!("{} Lineup\n{}", team.team.name, table.render());
+}
+
+fn display_game_lineups(game_id: i32) -> reqwest::Result<()> {
+    let box_score: BoxScore = get(box_score_url!(game_id))?.json()?;
+    let feed: Feed = get(game_feed_url!(game_id))?.json()?;
+
+    println!("{}", Table::builder().rows(vec![Row::new(get_game_state(&feed))]).build().render());
+    display_lineup(&feed, &box_score.teams.away);
+    display_lineup(&feed, &box_score.teams.home);
+    Ok(())
 }
 
 fn hitters_and_pitchers(team: &Team) -> (Vec<&Player>, Vec<&Player>) {
@@ -374,7 +607,7 @@ fn hitters_and_pitchers(team: &Team) -> (Vec<&Player>, Vec<&Player>) {
     (hitters, pitchers)
 }
 
-fn display_winning_and_losing_pitchers(away_pitchers: &Vec<&Player>, home_pitchers: &Vec<&Player>) {
+fn display_winning_and_losing_pitchers(away_pitchers: &[&Player], home_pitchers: &[&Player]) {
     let mut winning_pitcher = "".to_string();
     let mut losing_pitcher = "".to_string();
 
@@ -389,62 +622,85 @@ fn get_final_score(team_score: &Score) -> Vec<String> {
          team_score.errors.to_string(), team_score.leftOnBase.to_string()]
 }
 
-fn display_line_score(game_id: i32, feed: &Feed, line_score: &LineScore, away_team: &Team, home_team: &Team) -> reqwest::Result<()> {
-    const SCHEDULED_INNINGS: i32 = 9;
+fn get_inning_runs(score: &Score) -> String {
+    score.runs.map_or(" ".to_string(), |runs| runs.to_string())
+}
 
-    let mut innings = Table::new();
+fn line_score_table(innings: &[(String, String, String)], away_name: &str, home_name: &str, totals: Option<&TeamScores>) -> Table {
     let mut innings_header: Vec<String> = vec!["Team".to_string()];
-    innings_header.append(&mut line_score.innings.iter().map(|inning| inning.num.to_string()).collect());
-
-    let mut away_scores: Vec<String> = vec![away_team.team.name.clone()];
-    let mut home_scores: Vec<String> = vec![home_team.team.name.clone()];
-    for inning in &line_score.innings {
-        let away_score = &inning.away;
-        let away_score_option = away_score.runs;
-        if away_score_option.is_some() {
-            away_scores.push(away_score_option.unwrap_or(0).to_string());
-        }
-        else {
-            away_scores.push(" ".to_string());
-        }
+    let mut away_scores: Vec<String> = vec![away_name.to_string()];
+    let mut home_scores: Vec<String> = vec![home_name.to_string()];
+    for (num, away_runs, home_runs) in innings {
+        innings_header.push(num.clone());
+        away_scores.push(away_runs.clone());
+        home_scores.push(home_runs.clone());
+    }
 
-        let home_score = &inning.home;
-        let home_score_option = home_score.runs;
-        if home_score_option.is_some() {
-            home_scores.push(home_score_option.unwrap_or(0).to_string());
-        }
-        else if inning.num == SCHEDULED_INNINGS && &feed.gameData.status.abstractGameState == "Final" {
-            home_scores.push("X".to_string());
+    if let Some(totals) = totals {
+        innings_header.append(&mut vec!["R".to_string(), "H".to_string(), "E".to_string(), "LOB".to_string()]);
+        away_scores.append(&mut get_final_score(&totals.away));
+        home_scores.append(&mut get_final_score(&totals.home));
+    }
+
+    let mut table = Table::new();
+    table.add_row(Row::new(innings_header));
+    table.add_row(Row::new(away_scores));
+    table.add_row(Row::new(home_scores));
+    table
+}
+
+fn display_line_score(feed: &Feed, line_score: &LineScore, away_team: &Team, home_team: &Team) {
+    const CELL_PADDING: usize = 3;
+    const TOTAL_COLS_WIDTH: usize = 20;
+
+    let status = &feed.gameData.status;
+    let is_final = status.abstractGameState == "Final" && !is_suspended(status);
+    let scheduled_innings = line_score.scheduledInnings;
+
+    let mut innings: Vec<(String, String, String)> = line_score.innings.iter()
+        .map(|inning| (inning.num.to_string(), get_inning_runs(&inning.away), get_inning_runs(&inning.home)))
+        .collect();
+    if is_final {
+        // The home team skips its last half inning whenever it already leads, in any inning
+        if let Some((_, _, home_runs)) = innings.last_mut() {
+            if home_runs.trim().is_empty() {
+                *home_runs = "X".to_string();
+            }
         }
-        else {
-            home_scores.push(" ".to_string());
+    }
+    else {
+        for num in (innings.len() as i32 + 1)..=scheduled_innings {
+            innings.push((num.to_string(), " ".to_string(), " ".to_string()));
         }
     }
-    for i in (line_score.innings.len() + 1)..10 {
-        innings_header.push(i.to_string());
-        away_scores.push(" ".to_string());
-        home_scores.push(" ".to_string());
+
+    let name_width = away_team.team.name.len().max(home_team.team.name.len()) + CELL_PADDING;
+    let innings_width: usize = innings.iter()
+        .map(|(num, away_runs, home_runs)| num.len().max(away_runs.len()).max(home_runs.len()) + CELL_PADDING).sum();
+    let compact = name_width + innings_width + TOTAL_COLS_WIDTH > terminal_width();
+
+    let (away_name, home_name, chunk_size) = if compact {
+        (&away_team.team.abbreviation, &home_team.team.abbreviation, scheduled_innings.max(1) as usize)
     }
-    away_scores.append(&mut get_final_score(&line_score.teams.away));
-    home_scores.append(&mut get_final_score(&line_score.teams.home));
+    else {
+        (&away_team.team.name, &home_team.team.name, innings.len().max(1))
+    };
+    let chunks: Vec<&[(String, String, String)]> = if innings.is_empty() { vec![&innings[..]] }
+        else { innings.chunks(chunk_size).collect() };
 
-    innings_header.append(&mut vec!["R".to_string(), "H".to_string(), "E".to_string(), "LOB".to_string()]);
-    innings.add_row(Row::new(innings_header));
-    innings.add_row(Row::new(away_scores));
-    innings.add_row(Row::new(home_scores));
+    let tables: Vec<String> = chunks.iter().enumerate().map(|(i, chunk)| {
+        let totals = if i == chunks.len() - 1 { Some(&line_score.teams) } else { None };
+        line_score_table(chunk, away_name, home_name, totals).render()
+    }).collect();
 
     println!(
         "{}\n{}",
-        Table::builder().rows(vec![Row::new(get_game_state(&feed))]).build().render(),
-        innings.render()
+        Table::builder().rows(vec![Row::new(get_game_state(feed))]).build().render(),
+        tables.join("\n")
     );
-    if &feed.gameData.status.abstractGameState == "Live" {
-        display_win_probability(game_id, away_team, home_team)?;
-    }
-    Ok(())
 }
 
-fn display_games(games: &Vec<Game>) -> reqwest::Result<()> {
+fn display_games(games: &[Game]) -> reqwest::Result<()> {
     let mut game_feeds: Vec<(&Game, Feed)> = Vec::with_capacity(games.len());
     for game in games {
         let feed: Feed = get(game_feed_url!(game.gamePk))?.json()?;
@@ -480,15 +736,56 @@ fn display_games(games: &Vec<Game>) -> reqwest::Result<()> {
     Ok(())
 }
 
-fn display_win_probability(game_id: i32, away_team: &Team, home_team: &Team) -> reqwest::Result<()> {
-    let win_probability: Vec<WinProbability> = get(win_probability_url!(game_id))?.json()?;
-    let current_probability = &win_probability[win_probability.len() - 1];
+// Kept apart by role so a two-way player's plate appearances and pitching don't net out
+#[derive(Default)]
+struct PlayerWpa {
+    batting: HashMap<i32, f64>,
+    pitching: HashMap<i32, f64>
+}
+
+fn get_player_wpa(win_probability: &[WinProbability]) -> PlayerWpa {
+    let mut wpa = PlayerWpa::default();
+    for play in win_probability {
+        let batter_wpa = if play.about.isTopInning { -play.homeTeamWinProbabilityAdded }
+            else { play.homeTeamWinProbabilityAdded };
+        *wpa.batting.entry(play.matchup.batter.id).or_insert(0.0) += batter_wpa;
+        *wpa.pitching.entry(play.matchup.pitcher.id).or_insert(0.0) -= batter_wpa;
+    }
+    wpa
+}
+
+fn display_win_probability(win_probability: &[WinProbability], away_team: &Team, home_team: &Team) {
+    const CHART_HEIGHT: usize = 11;
+    const SWINGS: usize = 5;
+    const START_PROBABILITY: f64 = 50.0;
+
+    let Some(current_probability) = win_probability.last() else { return };
     println!(
         "Win Probability:\n{}: {:.1}%\n{}: {:.1}%\n", away_team.team.name,
         current_probability.awayTeamWinProbability, home_team.team.name,
         current_probability.homeTeamWinProbability
     );
-    Ok(())
+
+    let mut home_probabilities = vec![START_PROBABILITY];
+    home_probabilities.extend(win_probability.iter().map(|play| play.homeTeamWinProbability));
+    println!("{} Win Probability by Plate Appearance\n{}\n", home_team.team.name,
+        line_chart(&home_probabilities, 0.0, 100.0, CHART_HEIGHT, terminal_width(), |value| format!("{value:.0}%")));
+
+    let mut swings: Vec<&WinProbability> = win_probability.iter().collect();
+    swings.sort_by(|play0, play1| play1.homeTeamWinProbabilityAdded.abs()
+        .total_cmp(&play0.homeTeamWinProbabilityAdded.abs()));
+
+    let mut swing_table = Table::new();
+    swing_table.add_row(row!("Inning", "Team", "Swing", "Play"));
+    for play in swings.into_iter().take(SWINGS) {
+        let added = play.homeTeamWinProbabilityAdded;
+        let team = if added >= 0.0 { &home_team.team.abbreviation } else { &away_team.team.abbreviation };
+        swing_table.add_row(row!(
+            format!("{} {}", play.about.halfInning, play.about.inning), team,
+            format!("+{:.1}%", added.abs()), &play.result.description
+        ));
+    }
+    println!("Biggest Swings\n{}", swing_table.render());
 }
 
 pub(crate) fn display_game_stats(game_id: i32) -> reqwest::Result<()> {
@@ -507,27 +804,40 @@ pub(crate) fn display_game_stats(game_id: i32) -> reqwest::Result<()> {
         println!("{}", Table::builder().style(TableStyle::blank())
             .rows(rows![upcoming_game!(away_team, away_record, home_team, home_record, feed, "", "@", "")]).build().render()
         );
+        display_lineup(&feed, away_team);
+        display_lineup(&feed, home_team);
+        display_probable_pitchers(&feed, away_team, home_team)?;
     }
     else {
+        // Best effort, the box score is still shown without the chart and WPA columns when this can't be fetched
+        let win_probability: Option<Vec<WinProbability>> = get(win_probability_url!(game_id))
+            .and_then(|response| response.json()).ok();
+        let wpa = win_probability.as_deref().map(get_player_wpa);
+
         display_score!(away_team, home_team, line_score);
-        display_line_score(game_id, &feed, &line_score, away_team, home_team)?;
+        display_line_score(&feed, &line_score, away_team, home_team);
+        if let Some(win_probability) = &win_probability {
+            display_win_probability(win_probability, away_team, home_team);
+        }
         display_record!(away_team);
         display_record!(home_team);
         display_winning_and_losing_pitchers(&away_pitchers, &home_pitchers);
 
         const DIVIDER_LEN: usize = 128;
         println!("{}", "-".repeat(DIVIDER_LEN));
-        display_team_stats(away_team, &away_hitters, &away_pitchers);
+        display_team_stats(away_team, &away_hitters, &away_pitchers, wpa.as_ref());
         println!("{}", "-".repeat(DIVIDER_LEN));
-        display_team_stats(home_team, &home_hitters, &home_pitchers);
+        display_team_stats(home_team, &home_hitters, &home_pitchers, wpa.as_ref());
     }
     Ok(())
 }
 
-pub(crate) fn display_games_today() -> reqwest::Result<()> {
-    let mut schedule: Schedule = get(games_url!(""))?.json()?;
-    display_games(&mut schedule.dates[0].games)?;
-    Ok(())
+pub(crate) fn display_games_today(game_type: Option<&str>) -> Result<(), QueryError> {
+    let schedule: Schedule = get(games_url!("", game_type))?.json()?;
+    match schedule.dates.first() {
+        Some(date) => Ok(display_games(&date.games)?),
+        None => Err(QueryError::GameError("No games today".to_string()))
+    }
 }
 
 fn filter_games(schedule: Schedule, predicate: fn(&Game) -> bool) -> Vec<Game> {
@@ -542,6 +852,41 @@ fn filter_games(schedule: Schedule, predicate: fn(&Game) -> bool) -> Vec<Game> {
     games
 }
 
+// Every game of the regular season as (away id, home id, winner id), with no winner for games still to be played.
+// Given a YYYY-MM-DD date, games after it count as unplayed so the schedule matches standings as of that date
+pub(crate) fn get_league_schedule(season: i32, as_of: Option<&str>) -> reqwest::Result<Vec<(i32, i32, Option<i32>)>> {
+    let schedule: Schedule = get(league_schedule_url!(season))?.json()?;
+    let games = filter_games(schedule, |game| game.status.detailedState != "Postponed"
+        && game.status.detailedState != "Cancelled");
+
+    Ok(games.iter().map(|game| {
+        let (away, home) = (&game.teams.away, &game.teams.home);
+        let played = game.status.abstractGameState == "Final"
+            && as_of.is_none_or(|date| game.officialDate.as_str() <= date);
+        let winner = if !played { None }
+            else if home.score > away.score { Some(home.team.id) }
+            else { Some(away.team.id) };
+        (away.team.id, home.team.id, winner)
+    }).collect())
+}
+
+// The team's wins minus losses after its last game on each date it played
+pub(crate) fn get_season_record_by_date(team_id: i32, season: i32) -> reqwest::Result<Vec<(String, i32)>> {
+    let schedule: Schedule = get(season_games_url!(team_id, season, Some(REGULAR_SEASON)))?.json()?;
+    let games: Vec<Game> = filter_games(schedule, |game| &game.status.detailedState == "Final");
+
+    let mut records: Vec<(String, i32)> = Vec::new();
+    for game in &games {
+        let (team, _, _) = get_team_and_opp(team_id, game);
+        let above_500 = team.leagueRecord.wins - team.leagueRecord.losses;
+        match records.last_mut() {
+            Some((date, record)) if *date == game.officialDate => *record = above_500,
+            _ => records.push((game.officialDate.clone(), above_500))
+        }
+    }
+    Ok(records)
+}
+
 fn get_team_and_opp(team_id: i32, game: &Game) -> (&PlayingTeam, &PlayingTeam, &str) {
     let teams = &game.teams;
     let mut opp = &teams.away;
@@ -555,9 +900,57 @@ fn get_team_and_opp(team_id: i32, game: &Game) -> (&PlayingTeam, &PlayingTeam, &
     (team, opp, symbol)
 }
 
-fn display_past_games(team_id: i32, limit: usize) -> reqwest::Result<()> {
-    let schedule: Schedule = get(season_games_url!(team_id,  Utc::now().year()))?.json()?;
+fn matches_filters(team_id: i32, game: &Game, filters: &ResultFilters) -> bool {
+    const MONTH_RANGE: std::ops::Range<usize> = 5..7;
+
+    let (team, opp, _) = get_team_and_opp(team_id, game);
+    let is_home = game.teams.home.team.id == team_id;
+    let month = game.officialDate.get(MONTH_RANGE).and_then(|month| month.parse::<u32>().ok());
+    let is_extras = game.linescore.as_ref()
+        .is_some_and(|line_score| line_score.innings.len() as i32 > line_score.scheduledInnings);
+
+    (!filters.home || is_home) && (!filters.away || !is_home)
+        && filters.vs.is_none_or(|vs| opp.team.id == vs)
+        && filters.month.is_none_or(|filter_month| month == Some(filter_month))
+        && (!filters.one_run || (team.score - opp.score).abs() == 1)
+        && (!filters.extras || is_extras)
+}
+
+fn display_results_summary(team_id: i32, games: &[&Game]) {
+    let (mut wins, mut losses, mut runs_scored, mut runs_allowed) = (0, 0, 0, 0);
+    let (mut streak, mut longest_win_streak, mut longest_loss_streak) = (0i32, 0, 0);
+    for &game in games {
+        let (team, opp, _) = get_team_and_opp(team_id, game);
+        runs_scored += team.score;
+        runs_allowed += opp.score;
+        if team.score > opp.score {
+            wins += 1;
+            streak = streak.max(0) + 1;
+        }
+        else {
+            losses += 1;
+            streak = streak.min(0) - 1;
+        }
+        longest_win_streak = longest_win_streak.max(streak);
+        longest_loss_streak = longest_loss_streak.max(-streak);
+    }
+
+    let expected_pct = get_pythagorean_pct(runs_scored, runs_allowed);
+    let expected_wins = (expected_pct * games.len() as f64).round() as usize;
+
+    let mut summary = Table::new();
+    summary.add_row(row!("Games", "Record", "RS", "RA", "DIFF", "Pythag W-L", "Longest W", "Longest L"));
+    summary.add_row(row!(
+        games.len(), format!("{wins}-{losses}"), runs_scored, runs_allowed, runs_scored - runs_allowed,
+        format!("{}-{}", expected_wins, games.len() - expected_wins), longest_win_streak, longest_loss_streak
+    ));
+    println!("{}", summary.render());
+}
+
+fn display_past_games(team_id: i32, limit: usize, game_type: Option<&str>, season: i32, filters: &ResultFilters) -> reqwest::Result<()> {
+    let schedule: Schedule = get(season_games_url!(team_id, season, game_type))?.json()?;
     let games: Vec<Game> = filter_games(schedule, |game| &game.status.detailedState == "Final");
+    let games: Vec<&Game> = games.iter().filter(|game| matches_filters(team_id, game, filters)).collect();
     let mut start = 0;
     if limit < games.len() {
         start = games.len() - limit;
@@ -565,9 +958,9 @@ fn display_past_games(team_id: i32, limit: usize) -> reqwest::Result<()> {
 
     let mut game_results = Table::new();
     game_results.style = TableStyle::blank();
-    game_results.add_row(row!("Opponent", "Opp Record", "Result", "Record"));
+    game_results.add_row(row!("Date", "Opponent", "Opp Record", "Result", "Record"));
 
-    for game in &games[start..] {
+    for &game in &games[start..] {
         let (team, opp, symbol) = get_team_and_opp(team_id, game);
 
         let mut res = "W";
@@ -576,6 +969,7 @@ fn display_past_games(team_id: i32, limit: usize) -> reqwest::Result<()> {
         }
 
         game_results.add_row(row!(
+            &game.officialDate,
             format!("{} {}", symbol, opp.team.name),
             format!("({}-{})", opp.leagueRecord.wins, opp.leagueRecord.losses),
             format!("{} {}-{}", res, team.score, opp.score),
@@ -583,88 +977,307 @@ fn display_past_games(team_id: i32, limit: usize) -> reqwest::Result<()> {
         ));
     }
     println!("{}", game_results.render());
+    display_results_summary(team_id, &games);
+    Ok(())
+}
+
+fn get_probable_pitcher(pitcher: &Option<stats::Player>) -> reqwest::Result<String> {
+    match pitcher {
+        Some(pitcher) => Ok(format!("{} ({})", pitcher.fullName, get_pitcher_summary(pitcher.id)?)),
+        None => Ok("TBD".to_string())
+    }
+}
+
+// Summary from the season line hydrated on the schedule rather than a request per pitcher
+fn get_scheduled_probable_pitcher(pitcher: &Option<ProbablePitcher>) -> String {
+    let Some(pitcher) = pitcher else { return "TBD".to_string() };
+    let stat = pitcher.stats.iter()
+        .filter(|stats| stats["group"]["displayName"] == "pitching")
+        .find_map(|stats| serde_json::from_value::<Pitcher>(stats["splits"][0]["stat"].clone()).ok());
+    format!("{} ({})", pitcher.person.fullName, format_pitcher_summary(stat.as_ref()))
+}
+
+fn display_head_to_head(pitcher: &stats::Player, opp_team: &Team) -> reqwest::Result<()> {
+    let lineup = get_lineup(opp_team);
+    if lineup.is_empty() {
+        println!("{} lineup not yet posted\n", opp_team.team.name);
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.add_row(row!("Batter", "PA", "AB", "H", "HR", "BB", "SO", "AVG", "OPS"));
+    for batter in lineup {
+        match get_matchup_stats(batter.person.id, pitcher.id)? {
+            Some(stat) => table.add_row(row!(
+                &batter.person.fullName, stat.plateAppearances, stat.atBats, stat.hits, stat.homeRuns,
+                stat.baseOnBalls, stat.strikeOuts, &stat.avg, &stat.ops
+            )),
+            None => table.add_row(row!(&batter.person.fullName, 0, 0, 0, 0, 0, 0, "-", "-"))
+        }
+    }
+    println!("{} vs {} Lineup\n{}", pitcher.fullName, opp_team.team.name, table.render());
+    Ok(())
+}
+
+fn display_probable_pitchers(feed: &Feed, away_team: &Team, home_team: &Team) -> reqwest::Result<()> {
+    let probable_pitchers = &feed.gameData.probablePitchers;
+
+    let mut table = Table::new();
+    table.add_row(row!("Team", "Probable Pitcher"));
+    table.add_row(row!(&away_team.team.name, get_probable_pitcher(&probable_pitchers.away)?));
+    table.add_row(row!(&home_team.team.name, get_probable_pitcher(&probable_pitchers.home)?));
+    println!("{}", table.render());
+
+    if let Some(pitcher) = &probable_pitchers.away {
+        display_head_to_head(pitcher, home_team)?;
+    }
+    if let Some(pitcher) = &probable_pitchers.home {
+        display_head_to_head(pitcher, away_team)?;
+    }
     Ok(())
 }
 
-fn display_schedule(team_id: i32, limit: usize) -> reqwest::Result<()> {
-    let schedule: Schedule = get(season_games_url!(team_id,  Utc::now().year()))?.json()?;
+fn get_calendar_event(game: &Game) -> Option<Event> {
+    const GAME_HOURS: i64 = 3;
+
+    let start = chrono::DateTime::parse_from_rfc3339(&game.gameDate).ok()?.with_timezone(&Utc);
+    let broadcasts: Vec<String> = game.broadcasts.iter()
+        .map(|broadcast| format!("{} ({})", broadcast.name, broadcast.broadcastType)).collect();
+    let mut description = format!("{} ({}-{}) at {} ({}-{})",
+        game.teams.away.team.name, game.teams.away.leagueRecord.wins, game.teams.away.leagueRecord.losses,
+        game.teams.home.team.name, game.teams.home.leagueRecord.wins, game.teams.home.leagueRecord.losses);
+    if !broadcasts.is_empty() {
+        description.push_str(&format!("\nBroadcasts: {}", broadcasts.join(", ")));
+    }
+
+    Some(Event {
+        uid: format!("{}@statsapi.mlb.com", game.gamePk),
+        start,
+        end: start + Duration::hours(GAME_HOURS),
+        summary: format!("{} @ {}", game.teams.away.team.name, game.teams.home.team.name),
+        location: game.venue.as_ref().map_or("".to_string(), |venue| venue.name.clone()),
+        description
+    })
+}
+
+fn export_schedule(team_id: i32, team_name: &str, game_type: Option<&str>, path: &str) -> Result<(), QueryError> {
+    let schedule: Schedule = get(season_games_url!(team_id, Utc::now().year(), game_type))?.json()?;
+    let games: Vec<Game> = filter_games(schedule, |game| &game.status.abstractGameState == "Preview");
+    let events: Vec<Event> = games.iter().filter_map(get_calendar_event).collect();
+
+    write_calendar(path, &format!("{team_name} Schedule"), &events)?;
+    println!("Wrote {} games to {}", events.len(), path);
+    Ok(())
+}
+
+fn display_schedule(team_id: i32, limit: usize, game_type: Option<&str>) -> reqwest::Result<()> {
+    let schedule: Schedule = get(season_games_url!(team_id, Utc::now().year(), game_type))?.json()?;
     let games: Vec<Game> = filter_games(schedule, |game| &game.status.abstractGameState == "Preview");
     let upcoming_games: Vec<Game> = games.into_iter().take(limit).collect();
 
     let mut schedule_table = Table::new();
     schedule_table.style = TableStyle::blank();
+    schedule_table.add_row(row!("Opponent", "Opp Record", "Date", "Time", "Probable", "Opp Probable"));
 
     for game in &upcoming_games {
-        let (_team, opp, symbol) = get_team_and_opp(team_id, game);
+        let (team, opp, symbol) = get_team_and_opp(team_id, game);
 
         schedule_table.add_row(row!(
             format!("{} {}", symbol, opp.team.name),
             format!("({}-{})", opp.leagueRecord.wins, opp.leagueRecord.losses),
             format!("{}", &game.officialDate),
-            format!("{}", get_eastern_standard_time(&game.gameDate))
+            format!("{}", get_eastern_standard_time(&game.gameDate)),
+            get_scheduled_probable_pitcher(&team.probablePitcher),
+            get_scheduled_probable_pitcher(&opp.probablePitcher)
         ));
     }
     println!("{}", schedule_table.render());
     Ok(())
 }
 
-fn get_game_id(team: &String, date: &String) -> Result<i32, QueryError> {
+fn get_game_note(game: &Game) -> String {
+    if !game.resumedFromDate.is_empty() {
+        format!("Resumed from {}", game.resumedFromDate)
+    }
+    else if !game.resumeGameDate.is_empty() {
+        format!("Suspended, resumes {}", game.resumeGameDate)
+    }
+    else {
+        game.status.detailedState.clone()
+    }
+}
+
+fn pick_game(team_id: i32, games: &[Game]) -> Result<usize, QueryError> {
+    // Scripted or piped use can't answer a prompt, so it gets game 1 like a single game day
+    if !stdin().is_terminal() || !stdout().is_terminal() {
+        return Ok(0);
+    }
+
+    let mut picker = Table::new();
+    picker.style = TableStyle::blank();
+    for (i, game) in games.iter().enumerate() {
+        let (team, opp, symbol) = get_team_and_opp(team_id, game);
+        let result = match game.status.abstractGameState.as_str() {
+            "Preview" => get_eastern_standard_time(&game.gameDate),
+            _ => format!("{}-{}", team.score, opp.score)
+        };
+        picker.add_row(row!(
+            format!("Game {}", i + 1), format!("{} {}", symbol, opp.team.name), result, get_game_note(game)
+        ));
+    }
+    println!("{}", picker.render());
+
+    print!("Select game [1]: ");
+    stdout().flush()?;
+    let mut selection = String::new();
+    stdin().read_line(&mut selection)?;
+    match selection.trim() {
+        "" => Ok(0),
+        number => match number.parse::<usize>() {
+            Ok(game_number) if (1..=games.len()).contains(&game_number) => Ok(game_number - 1),
+            _ => Err(QueryError::GameError(format!("No game {number} in picker")))
+        }
+    }
+}
+
+fn get_game_id(team: &String, date: &String, game_number: Option<usize>, game_type: Option<&str>) -> Result<i32, QueryError> {
     let (_, team_id) = get_team(team)?;
 
     let today: bool;
     let schedule: Schedule = if date.is_empty() {
         today = true;
-        get(games_url!(format!("&teamId={team_id}")))?.json()?
+        get(games_url!(format!("&teamId={team_id}"), game_type))?.json()?
     }
     else {
         today = false;
-        get(games_url!(format!("&teamId={team_id}&startDate={date}&endDate={date}")))?.json()?
+        get(games_url!(format!("&teamId={team_id}&startDate={date}&endDate={date}"), game_type))?.json()?
     };
-    if schedule.dates.len() > 0 {
-       return Ok(schedule.dates[0].games[0].gamePk);
-    }
-    let error_message = if today {
-        format!("No games for {team} today")
+    let day = if today { "today".to_string() } else { format!("on {date}") };
+
+    // A suspended game can be listed on both its original and resumed dates under the same gamePk
+    let mut games: Vec<Game> = filter_games(schedule, |_| true);
+    games.sort_by_key(|game| (game.gameNumber, game.gamePk));
+    games.dedup_by_key(|game| game.gamePk);
+    if games.is_empty() {
+        return Err(QueryError::GameError(format!("No games for {team} {day}")));
     }
-    else {
-        format!("No games for {team} on {date}")
+
+    let index = match game_number {
+        Some(number) if (1..=games.len()).contains(&number) => number - 1,
+        Some(number) => return Err(QueryError::GameError(format!("No game {number} for {team} {day}"))),
+        None if games.len() > 1 => pick_game(team_id, &games)?,
+        None => 0
     };
-    Err(QueryError::GameError(error_message))
+    Ok(games[index].gamePk)
 }
 
-pub(crate) fn games_query(query: &Vec<String>) -> Result<(), QueryError> {
+fn get_game_number(options: &HashMap<String, String>) -> Result<Option<usize>, QueryError> {
+    const GAME_OPTION: &str = "game";
+
+    match options.get(GAME_OPTION) {
+        Some(number) => Ok(Some(number.parse::<usize>()
+            .map_err(|_| QueryError::OptionError(GAME_OPTION.to_string(), number.clone()))?)),
+        None => Ok(None)
+    }
+}
+
+pub(crate) fn lineup_query(query: &[String]) -> Result<(), QueryError> {
     const TEAM_INDEX: usize = 2;
     const DATE_INDEX: usize = 3;
+    const MIN_LENGTH: usize = 3;
+
+    let (query, options) = split_options(query);
+    if query.len() < MIN_LENGTH {
+        return Err(QueryError::QueryTooShort("No Team Provided".to_string()));
+    }
+    let game_number = get_game_number(&options)?;
+    let game_type = get_schedule_game_type(&options)?;
+
+    let default = empty!();
+    let team = get_query_param!(query, TEAM_INDEX, default);
+    let date = query.get(DATE_INDEX).unwrap_or(default);
+    let game_id = get_game_id(&team, date, game_number, game_type)?;
+    display_game_lineups(game_id)?;
+    Ok(())
+}
+
+pub(crate) fn games_query(query: &[String]) -> Result<(), QueryError> {
+    const TEAM_INDEX: usize = 2;
+    const DATE_INDEX: usize = 3;
+
+    let (query, options) = split_options(query);
+    let game_number = get_game_number(&options)?;
+    let game_type = get_schedule_game_type(&options)?;
 
     let default = empty!();
     let team = get_query_param!(query, TEAM_INDEX, default);
     match team.as_str() {
-        "" => {
-            display_games_today()?;
-            Ok(())
-        },
+        "" => display_games_today(game_type),
         _ => {
             let date = query.get(DATE_INDEX).unwrap_or(default);
-            let game_id = get_game_id(&team, date)?;
+            let game_id = get_game_id(&team, date, game_number, game_type)?;
             display_game_stats(game_id)?;
             Ok(())
         }
     }
 }
 
-pub(crate) fn season_games_query(query: &Vec<String>) -> Result<(), QueryError> {
+fn get_result_filters(options: &HashMap<String, String>) -> Result<ResultFilters, QueryError> {
+    const VS_OPTION: &str = "vs";
+    const MONTH_OPTION: &str = "month";
+    const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+
+    let vs = match options.get(VS_OPTION) {
+        Some(team) => Some(get_team(&team.to_ascii_lowercase())?.1),
+        None => None
+    };
+    let month = match options.get(MONTH_OPTION) {
+        Some(month) => {
+            let name = month.to_ascii_lowercase();
+            let number = month.parse::<u32>().ok()
+                .or(MONTHS.iter().position(|&prefix| name.starts_with(prefix)).map(|i| i as u32 + 1))
+                .filter(|number| (1..=12).contains(number));
+            Some(number.ok_or(QueryError::OptionError(MONTH_OPTION.to_string(), month.clone()))?)
+        },
+        None => None
+    };
+
+    Ok(ResultFilters {
+        home: options.contains_key("home"),
+        away: options.contains_key("away"),
+        vs,
+        month,
+        one_run: options.contains_key("one-run"),
+        extras: options.contains_key("extras")
+    })
+}
+
+pub(crate) fn season_games_query(query: &[String]) -> Result<(), QueryError> {
     const TYPE_INDEX: usize = 1;
     const TEAM_INDEX: usize = 2;
     const LIMIT_INDEX: usize = 3;
     const DEFAULT_LIMIT: usize = 8;
+    const ICS_OPTION: &str = "ics";
 
+    let (query, options) = split_options(query);
+    let game_type = get_schedule_game_type(&options)?;
+    let season = get_season(&options)?;
     let default = empty!();
-    let (_, team_id) = get_team(&get_query_param!(query, TEAM_INDEX, default))?;
+    let (team_entry, team_id) = get_team(&get_query_param!(query, TEAM_INDEX, default))?;
     let limit = &query.get(LIMIT_INDEX).unwrap_or(&DEFAULT_LIMIT.to_string()).parse::<usize>().unwrap_or(DEFAULT_LIMIT);
 
     match query[TYPE_INDEX].to_ascii_lowercase().as_str() {
-        "r" | "results" => display_past_games(team_id, *limit)?,
-        _ => display_schedule(team_id, *limit)?
+        "r" | "results" => {
+            let filters = get_result_filters(&options)?;
+            display_past_games(team_id, *limit, game_type, season, &filters)?
+        },
+        _ => match options.get(ICS_OPTION) {
+            Some(path) => {
+                let name = team_entry[1..team_entry.len() - 1].join(" ");
+                export_schedule(team_id, &name, game_type, path)?
+            },
+            None => display_schedule(team_id, *limit, game_type)?
+        }
     }
     Ok(())
 }
@@ -3,7 +3,9 @@ use term_table::table_cell::TableCell;
 use term_table::row::Row;
 use term_table::{row, Table};
 use reqwest::blocking::get;
-use crate::stats::{Split, Stat};
+use crate::sabermetrics::{sabermetric_hitting_header, sabermetric_hitting_row, sabermetric_inputs, HittingLine};
+use crate::query::QueryError;
+use crate::stats::{no_stats_error, Split, Stat};
 
 #[derive(Deserialize)]
 pub(crate) struct BasicHittingStats {
@@ -20,6 +22,8 @@ struct YearByYearStats {
     stats: (Stat<Batter>, Stat<Batter>, Stat<AdvancedBatter>, Stat<AdvancedBatter>)
 }
 
+type HittingStatGroups = (Vec<Stat<Batter>>, Vec<Stat<AdvancedBatter>>);
+
 #[derive(Deserialize)]
 pub(crate) struct Batter {
     pub(crate) plateAppearances: i32,
@@ -64,8 +68,40 @@ struct AdvancedBatter {
     walksPerStrikeout: String,
 }
 
+#[derive(Deserialize)]
+struct MatchupStats {
+    stats: Vec<Stat<MatchupBatter>>
+}
+
+#[derive(Deserialize)]
+pub(crate) struct MatchupBatter {
+    #[serde(default)]
+    pub(crate) plateAppearances: i32,
+    #[serde(default)]
+    pub(crate) atBats: i32,
+    #[serde(default)]
+    pub(crate) hits: i32,
+    #[serde(default)]
+    pub(crate) homeRuns: i32,
+    #[serde(default)]
+    pub(crate) baseOnBalls: i32,
+    #[serde(default)]
+    pub(crate) strikeOuts: i32,
+    #[serde(default)]
+    pub(crate) avg: String,
+    #[serde(default)]
+    pub(crate) ops: String
+}
+
+macro_rules! matchup_url {
+    ($batter_id:expr, $pitcher_id:expr) => {
+        format!("https://statsapi.mlb.com/api/v1/people/{}/stats?stats=vsPlayerTotal&opposingPlayerId={}&group=hitting",
+            $batter_id, $pitcher_id)
+    };
+}
+
 macro_rules! career_years_url {
-    () => { "https://statsapi.mlb.com/api/v1/people/{}/stats?stats=yearByYear,career,yearByYearAdvanced,careerAdvanced&group=hitting" };
+    () => { "https://statsapi.mlb.com/api/v1/people/{}/stats?stats=yearByYear,career,yearByYearAdvanced,careerAdvanced&group=hitting&gameType={}" };
 }
 
 macro_rules! basic_season_stats_url {
@@ -73,7 +109,7 @@ macro_rules! basic_season_stats_url {
 }
 
 macro_rules! advanced_group_url {
-    () => { "https://statsapi.mlb.com/api/v1/people/{}/stats?stats={},{}Advanced&group=hitting" };
+    () => { "https://statsapi.mlb.com/api/v1/people/{}/stats?stats={},{}Advanced&group=hitting&gameType={}" };
 }
 
 macro_rules! basic_hitting_header {
@@ -119,24 +155,38 @@ fn advanced_hitting_row(advanced_split: &Split<AdvancedBatter>, stat_group: &Bat
     )
 }
 
+pub(crate) fn get_matchup_stats(batter_id: i32, pitcher_id: i32) -> reqwest::Result<Option<MatchupBatter>> {
+    let stats: MatchupStats = get(matchup_url!(batter_id, pitcher_id))?.json()?;
+    Ok(stats.stats.into_iter().next().and_then(|stat| stat.splits.into_iter().next()).map(|split| split.stat))
+}
+
 pub(crate) fn get_basic_season_hitting_stats(player_id: i32) -> reqwest::Result<BasicHittingStats> {
     let stats: BasicHittingStats = get(format!(basic_season_stats_url!(), player_id))?.json()?;
     Ok(stats)
 }
 
-fn get_hitting_stats(player_id: i32, season_type: &str) -> reqwest::Result<(Vec<Stat<Batter>>, Vec<Stat<AdvancedBatter>>)> {
+pub(crate) fn get_year_by_year_hitting_stats(player_id: i32, game_type: &str) -> reqwest::Result<Vec<Split<Batter>>> {
+    let stats: YearByYearStats = get(format!(career_years_url!(), player_id, game_type))?.json()?;
+    Ok(stats.stats.0.splits)
+}
+
+fn get_hitting_stats(player_id: i32, season_type: &str, game_type: &str) -> reqwest::Result<HittingStatGroups> {
     if season_type == "yearByYear" {
-        let url = format!(career_years_url!(), player_id);
+        let url = format!(career_years_url!(), player_id, game_type);
         let stats: YearByYearStats = get(url)?.json()?;
         return Ok((vec![stats.stats.0, stats.stats.1], vec![stats.stats.2, stats.stats.3]));
     }
-    let url = format!(advanced_group_url!(), player_id, season_type, season_type);
+    let url = format!(advanced_group_url!(), player_id, season_type, season_type, game_type);
     let stats: FullHittingStats = get(url)?.json()?;
     Ok((vec![stats.stats.0], vec![stats.stats.1]))
 }
 
-pub(crate) fn display_hitting_stats(player_id: i32, season_type: &str) -> reqwest::Result<()> {
-    let stats: (Vec<Stat<Batter>>, Vec<Stat<AdvancedBatter>>) = get_hitting_stats(player_id, season_type)?;
+pub(crate) fn display_hitting_stats(player_id: i32, season_type: &str, game_type: &str, show_sabermetrics: bool)
+    -> Result<(), QueryError> {
+    let stats: HittingStatGroups = get_hitting_stats(player_id, season_type, game_type)?;
+    let Some(first_split) = stats.0.first().and_then(|stat| stat.splits.first()) else {
+        return Err(no_stats_error(season_type, game_type));
+    };
 
     let mut table0 = Table::new();
     table0.add_row(basic_hitting_header!("Year"));
@@ -144,24 +194,34 @@ pub(crate) fn display_hitting_stats(player_id: i32, season_type: &str) -> reqwes
     let mut table1 = Table::new();
     table1.add_row(advanced_hitting_header!("Year"));
 
+    let (league_constants, mut park_factors) = sabermetric_inputs(show_sabermetrics);
+    let mut table2 = Table::new();
+    table2.add_row(sabermetric_hitting_header!("Year"));
+
     let reg_stats = &stats.0;
     let advanced_stats = &stats.1;
-    for i in 0..reg_stats.len() {
-        let reg_splits = &reg_stats[i].splits;
-        let advanced_splits = &advanced_stats[i].splits;
+    for (i, reg_stat) in reg_stats.iter().enumerate() {
+        let advanced_splits = &advanced_stats.get(i).ok_or(no_stats_error(season_type, game_type))?.splits;
 
-        for j in 0..reg_splits.len() {
-            let split = &reg_splits[j];
+        for (j, split) in reg_stat.splits.iter().enumerate() {
             let stat_group = &split.stat;
             table0.add_row(basic_hitting_row!(&split.season, stat_group));
 
-            let advanced_split = &advanced_splits[j];
+            let advanced_split = advanced_splits.get(j).ok_or(no_stats_error(season_type, game_type))?;
             let advanced_stat_group = &advanced_split.stat;
             table1.add_row(advanced_hitting_row(advanced_split, stat_group, advanced_stat_group));
+            if show_sabermetrics {
+                let park_factor = split.team.as_ref().and_then(|team| park_factors.get(&split.season, team.id));
+                table2.add_row(sabermetric_hitting_row(&split.season, &HittingLine::from(stat_group),
+                    league_constants.get(&split.season), park_factor));
+            }
         }
     }
 
-    println!("\nPlayer: {}\n\nStandard Batting:\n{}", &stats.0[0].splits[0].player.fullName, table0.render());
+    println!("\nPlayer: {}\n\nStandard Batting:\n{}", &first_split.player.fullName, table0.render());
     println!("Advanced Batting:\n{}", table1.render());
+    if show_sabermetrics {
+        println!("Sabermetrics:\n{}", table2.render());
+    }
     Ok(())
 }
@@ -0,0 +1,111 @@
+pub(crate) struct Cell {
+    pub(crate) data_stat: String,
+    pub(crate) text: String
+}
+
+pub(crate) type HtmlTable = Vec<Vec<Cell>>;
+
+// Attributes are read as name and value pairs so a name only matches whole, "data-stat" never matches "xdata-stat".
+// Values may be double quoted, single quoted or bare
+fn get_attribute(attributes: &str, name: &str) -> Option<String> {
+    let mut rest = attributes.trim_start();
+    while !rest.is_empty() {
+        let name_end = rest.find(|char: char| char == '=' || char.is_whitespace()).unwrap_or(rest.len());
+        let (attribute, after_name) = rest.split_at(name_end);
+        let after_name = after_name.trim_start();
+        let (value, after_value) = match after_name.strip_prefix('=').map(|value| value.trim_start()) {
+            Some(value) => match value.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let end = value[1..].find(quote).map_or(value.len(), |end| end + 1);
+                    (&value[1..end], value.get(end + 1..).unwrap_or(""))
+                },
+                _ => value.split_at(value.find(char::is_whitespace).unwrap_or(value.len()))
+            },
+            None => ("", after_name)
+        };
+        if attribute.eq_ignore_ascii_case(name) {
+            return Some(value.to_string());
+        }
+        rest = after_value.trim_start();
+    }
+    None
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&nbsp;", " ").replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&amp;", "&")
+}
+
+// Some sites ship secondary tables inside HTML comments and reveal them with scripts, so comment markers are dropped
+// before parsing to pick those tables up as well
+pub(crate) fn parse_tables(html: &str) -> Vec<HtmlTable> {
+    let html = html.replace("<!--", "").replace("-->", "");
+
+    let mut tables: Vec<HtmlTable> = Vec::new();
+    let mut table: Option<HtmlTable> = None;
+    let mut cell: Option<Cell> = None;
+    let mut rest = html.as_str();
+    while let Some(tag_start) = rest.find('<') {
+        if let Some(cell) = cell.as_mut() {
+            cell.text.push_str(&rest[..tag_start]);
+        }
+        let Some(tag_len) = rest[tag_start..].find('>') else { break };
+        let tag = &rest[tag_start + 1..tag_start + tag_len];
+        rest = &rest[tag_start + tag_len + 1..];
+
+        let (name, attributes) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+        match name.to_ascii_lowercase().as_str() {
+            "table" => table = Some(Vec::new()),
+            "/table" => tables.extend(table.take()),
+            "tr" => if let Some(table) = table.as_mut() { table.push(Vec::new()) },
+            "th" | "td" => cell = Some(Cell {
+                data_stat: get_attribute(attributes, "data-stat").unwrap_or_default(),
+                text: String::new()
+            }),
+            "/th" | "/td" => if let (Some(mut finished), Some(row)) = (cell.take(), table.as_mut().and_then(|table| table.last_mut())) {
+                finished.text = decode_entities(finished.text.trim());
+                row.push(finished);
+            },
+            _ => ()
+        }
+    }
+    tables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attributes_match_whole_names() {
+        let attributes = r#"class="right " xdata-stat="wrong" data-stat="G" csk=5"#;
+        assert_eq!(get_attribute(attributes, "data-stat").as_deref(), Some("G"));
+        assert_eq!(get_attribute(attributes, "stat"), None);
+        assert_eq!(get_attribute(attributes, "csk").as_deref(), Some("5"));
+    }
+
+    #[test]
+    fn attributes_allow_single_quotes_and_spacing() {
+        let attributes = "scope='row' data-stat = 'year_ID' nowrap";
+        assert_eq!(get_attribute(attributes, "data-stat").as_deref(), Some("year_ID"));
+        assert_eq!(get_attribute(attributes, "nowrap").as_deref(), Some(""));
+    }
+
+    #[test]
+    fn tables_inside_comments_are_parsed() {
+        let tables = parse_tables(include_str!("../tests/fixtures/bat.shtml"));
+        assert_eq!(tables.len(), 2);
+
+        let averages = &tables[1];
+        let header = &averages[1];
+        assert_eq!((header[0].data_stat.as_str(), header[0].text.as_str()), ("year_ID", "Year"));
+        let first_season = &averages[2];
+        assert_eq!(first_season[0].text, "2024");
+        assert_eq!((first_season[5].data_stat.as_str(), first_season[5].text.as_str()), ("R", "4.39"));
+    }
+
+    #[test]
+    fn entities_are_decoded_and_text_trimmed() {
+        let tables = parse_tables("<table><tr><td data-stat=\"team\"> Cardinals &amp; Cubs&nbsp;</td></tr></table>");
+        assert_eq!(tables[0][0][0].text, "Cardinals & Cubs ");
+    }
+}
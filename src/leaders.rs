@@ -1,11 +1,16 @@
+use std::collections::HashMap;
 use reqwest::blocking::get;
 use serde::Deserialize;
 use term_table::{row, Table, rows};
 use term_table::row::Row;
 use term_table::table_cell::{Alignment, TableCell};
-use phf;
 use phf_macros::phf_map;
-use crate::query::{empty, get_query_param, QueryError};
+use crate::formula::{get_stat_values, parse_formula, Formula};
+use crate::parks::ParkFactors;
+use crate::sabermetrics::{get_hitting_values, get_pitching_values, sabermetric_hitting_header, sabermetric_hitting_row, sabermetric_inputs,
+    sabermetric_pitching_header, sabermetric_pitching_row, HittingLine, LeagueConstants, PitchingLine, LEAGUE_RELATIVE_STATS};
+use crate::query::{empty, get_game_type, get_query_param, get_season, split_options, QueryError};
+use crate::teams::get_team;
 
 const HEADER_MAP: phf::Map<&'static str, &'static str> = phf_map! {
     "battingAverage" => "Batting Average Leaders",
@@ -18,19 +23,80 @@ const HEADER_MAP: phf::Map<&'static str, &'static str> = phf_map! {
     "strikeouts" => "Strikeout Leaders",
     "walksAndHitsPerInningPitched" => "WHIP Leaders",
     "saves" => "Saves Leaders",
+    "onBasePercentage" => "OBP Leaders",
+    "sluggingPercentage" => "SLG Leaders",
+    "onBasePlusSlugging" => "OPS Leaders",
+    "inningsPitched" => "Innings Pitched Leaders",
+    "strikeoutsPer9Inn" => "K/9 Leaders",
+    "walksPer9Inn" => "BB/9 Leaders",
+    "hitsPer9Inn" => "H/9 Leaders",
+    "homeRunsPer9" => "HR/9 Leaders",
+    "strikeoutWalkRatio" => "K/BB Leaders",
+};
+
+// Friendly names for the categories listed by the leagueLeaderTypes endpoint, along with the stat group
+// they rank so that categories shared by hitters and pitchers are unambiguous
+const CATEGORY_ALIASES: phf::Map<&'static str, (&'static str, &'static str)> = phf_map! {
+    "avg" => ("battingAverage", "hitting"),
+    "obp" => ("onBasePercentage", "hitting"),
+    "slg" => ("sluggingPercentage", "hitting"),
+    "ops" => ("onBasePlusSlugging", "hitting"),
+    "hr" => ("homeRuns", "hitting"),
+    "rbi" => ("runsBattedIn", "hitting"),
+    "h" => ("hits", "hitting"),
+    "r" => ("runs", "hitting"),
+    "2b" => ("doubles", "hitting"),
+    "3b" => ("triples", "hitting"),
+    "tb" => ("totalBases", "hitting"),
+    "bb" => ("walks", "hitting"),
+    "k" => ("strikeouts", "hitting"),
+    "sb" => ("stolenBases", "hitting"),
+    "cs" => ("caughtStealing", "hitting"),
+    "xbh" => ("extraBaseHits", "hitting"),
+    "hbp" => ("hitByPitches", "hitting"),
+    "pa" => ("plateAppearances", "hitting"),
+    "wins" => ("wins", "pitching"),
+    "w" => ("wins", "pitching"),
+    "l" => ("losses", "pitching"),
+    "era" => ("earnedRunAverage", "pitching"),
+    "saves" => ("saves", "pitching"),
+    "sv" => ("saves", "pitching"),
+    "hld" => ("holds", "pitching"),
+    "so" => ("strikeouts", "pitching"),
+    "whip" => ("walksAndHitsPerInningPitched", "pitching"),
+    "ip" => ("inningsPitched", "pitching"),
+    "k9" => ("strikeoutsPer9Inn", "pitching"),
+    "bb9" => ("walksPer9Inn", "pitching"),
+    "h9" => ("hitsPer9Inn", "pitching"),
+    "hr9" => ("homeRunsPer9", "pitching"),
+    "kbb" => ("strikeoutWalkRatio", "pitching"),
+    "gs" => ("gamesStarted", "pitching"),
+    "cg" => ("completeGames", "pitching"),
+    "sho" => ("shutouts", "pitching"),
+    "pbb" => ("walks", "pitching"),
+    "phr" => ("homeRuns", "pitching"),
 };
 
 #[derive(Deserialize)]
 struct Leaders {
+    #[serde(alias = "teamLeaders")]
     leagueLeaders: Vec<Category>
 }
 
 #[derive(Deserialize)]
 struct Category {
     leaderCategory: String,
+    #[serde(default)]
+    statGroup: String,
+    #[serde(default)]
     leaders: Vec<Player>
 }
 
+#[derive(Deserialize)]
+struct LeaderType {
+    displayName: String
+}
+
 #[derive(Deserialize)]
 struct Player {
     rank: i32,
@@ -41,97 +107,467 @@ struct Player {
 
 #[derive(Deserialize)]
 struct Team {
+    #[serde(default)]
+    id: i32,
     name: String
 }
 
 #[derive(Deserialize)]
 struct Person {
-    fullName: String
+    #[serde(default)]
+    id: i32,
+    fullName: String,
+    primaryPosition: Option<Position>
+}
+
+#[derive(Deserialize)]
+struct Position {
+    abbreviation: String
+}
+
+#[derive(Deserialize)]
+struct BulkStats {
+    stats: Vec<BulkStat>
+}
+
+#[derive(Deserialize)]
+struct BulkStat {
+    splits: Vec<BulkSplit>
+}
+
+#[derive(Deserialize)]
+struct BulkSplit {
+    team: Option<Team>,
+    player: Person,
+    stat: serde_json::Map<String, serde_json::Value>
+}
+
+struct CustomBoard {
+    formula: Formula,
+    name: String,
+    group: String,
+    min_plate_appearances: Option<f64>,
+    min_innings: Option<f64>,
+    ascending: bool
+}
+
+struct LeaderFilters {
+    game_type: &'static str,
+    season: i32,
+    league_id: Option<i32>,
+    team_id: Option<i32>,
+    position: Option<String>,
+    player_pool: Option<&'static str>,
+    stat_type: Option<&'static str>,
+    show_sabermetrics: bool
+}
+
+impl LeaderFilters {
+    // Career and all-time single season boards span every season so the season is left out
+    fn get_params(&self, group: Option<&str>) -> String {
+        let mut params = format!("&gameType={}", self.game_type);
+        if self.stat_type.is_none() {
+            params.push_str(&format!("&season={}", self.season));
+        }
+        let optional_params = [
+            ("statGroup", group.map(|group| group.to_string())),
+            ("leagueId", self.league_id.map(|league_id| league_id.to_string())),
+            ("playerPool", self.player_pool.map(|player_pool| player_pool.to_string())),
+            ("statType", self.stat_type.map(|stat_type| stat_type.to_string())),
+            ("hydrate", self.position.as_ref().map(|_| "person".to_string()))
+        ];
+        for (name, value) in optional_params {
+            if let Some(value) = value {
+                params.push_str(&format!("&{name}={value}"));
+            }
+        }
+        params
+    }
+
+    fn get_stats_params(&self) -> String {
+        let mut params = format!("&gameType={}", self.game_type);
+        if self.stat_type.is_none() {
+            params.push_str(&format!("&season={}", self.season));
+        }
+        let optional_params = [
+            ("leagueId", self.league_id.map(|league_id| league_id.to_string())),
+            ("teamId", self.team_id.map(|team_id| team_id.to_string())),
+            ("position", self.position.clone())
+        ];
+        for (name, value) in optional_params {
+            if let Some(value) = value {
+                params.push_str(&format!("&{name}={value}"));
+            }
+        }
+        params
+    }
+
+    // The bulk stats endpoint has no all-time single season view, so those boards can't be ranked or extended locally
+    fn get_bulk_stat_type(&self, name: &str) -> Result<&'static str, QueryError> {
+        match self.stat_type {
+            None => Ok("season"),
+            Some("career") => Ok("career"),
+            Some(_) => Err(QueryError::OptionError("single-season".to_string(), name.to_string()))
+        }
+    }
+
+    fn matches_position(&self, person: &Person) -> bool {
+        match (&self.position, &person.primaryPosition) {
+            (None, _) => true,
+            (Some(position), Some(primary_position)) => position.eq_ignore_ascii_case(&primary_position.abbreviation),
+            (Some(_), None) => false
+        }
+    }
 }
 
 enum Stats {
-    ALL,
+    All,
     Batting,
     Pitching,
-    Stat(&'static str, &'static str)
+    Stat(String, Option<String>),
+    Custom(CustomBoard),
+    List
 }
 
 macro_rules! leaders_url {
-    ($categories:expr, $group:expr, $limit:expr) => {
-        format!("https://statsapi.mlb.com/api/v1/stats/leaders?leaderCategories={}&statGroup={}&limit={}", $categories, $group, $limit)
+    ($categories:expr, $limit:expr, $params:expr) => {
+        format!("https://statsapi.mlb.com/api/v1/stats/leaders?leaderCategories={}&limit={}{}", $categories, $limit, $params)
+    };
+}
+
+macro_rules! team_leaders_url {
+    ($team_id:expr, $categories:expr, $limit:expr, $params:expr) => {
+        format!("https://statsapi.mlb.com/api/v1/teams/{}/leaders?leaderCategories={}&limit={}{}", $team_id, $categories, $limit, $params)
+    };
+}
+
+macro_rules! bulk_stats_url {
+    ($stat_type:expr, $group:expr, $player_pool:expr, $params:expr) => {
+        format!("https://statsapi.mlb.com/api/v1/stats?stats={}&group={}&playerPool={}&limit=5000{}", $stat_type, $group, $player_pool, $params)
+    };
+}
+
+macro_rules! leader_types_url {
+    () => {
+        "https://statsapi.mlb.com/api/v1/leagueLeaderTypes"
     };
 }
 
 macro_rules! display_batting_leaders {
-    ($limit:expr) => {{
+    ($limit:expr, $filters:expr) => {{
         println!("\n{}", Table::builder().rows(rows![row!["Batting Leaders"]]).build().render());
-        display_leader_stats("battingAverage,homeRuns,runsBattedIn,hits,stolenBases", "hitting", $limit)?;
+        display_leader_stats("battingAverage,homeRuns,runsBattedIn,hits,stolenBases", Some("hitting"), $limit, $filters)?;
     }};
 }
 
 macro_rules! display_pitching_leaders {
-    ($limit:expr) => {{
+    ($limit:expr, $filters:expr) => {{
         println!("\n{}", Table::builder().rows(rows![row!["Pitching Leaders"]]).build().render());
-        display_leader_stats("wins,era,strikeOuts,whip,saves", "pitching", $limit)?;
+        display_leader_stats("wins,era,strikeOuts,whip,saves", Some("pitching"), $limit, $filters)?;
     }};
 }
 
-fn display_leader_stats(categories: &str, group: &str, limit: i32) -> reqwest::Result<()> {
+// Categories without a friendly header are spelled out from their camel case name, e.g. "groundOuts" as "Ground Outs Leaders"
+fn get_header(category: &str) -> String {
+    if let Some(header) = HEADER_MAP.get(category) {
+        return header.to_string();
+    }
+    let mut header = String::new();
+    for (i, char) in category.chars().enumerate() {
+        if i == 0 {
+            header.push(char.to_ascii_uppercase());
+            continue;
+        }
+        if char.is_ascii_uppercase() || (char.is_ascii_digit() && !header.ends_with(|prev: char| prev.is_ascii_digit())) {
+            header.push(' ');
+        }
+        header.push(char);
+    }
+    format!("{header} Leaders")
+}
+
+fn get_leader_types() -> reqwest::Result<Vec<String>> {
+    let leader_types: Vec<LeaderType> = get(leader_types_url!())?.json()?;
+    Ok(leader_types.into_iter().map(|leader_type| leader_type.displayName).collect())
+}
+
+fn display_leader_types() -> reqwest::Result<()> {
+    let mut table = Table::new();
+    table.add_row(row!("Category", "Aliases"));
+    for category in get_leader_types()? {
+        let mut aliases: Vec<String> = CATEGORY_ALIASES.entries()
+            .filter(|(_, (name, _))| name.eq_ignore_ascii_case(&category))
+            .map(|(alias, (_, group))| format!("{alias} ({group})")).collect();
+        aliases.sort();
+        table.add_row(row!(&category, aliases.join(", ")));
+    }
+    println!("{}", table.render());
+    Ok(())
+}
+
+fn get_custom_board(formula: &str, options: &HashMap<String, String>) -> Result<CustomBoard, QueryError> {
+    const GROUP_OPTION: &str = "group";
+    const MIN_PA_OPTION: &str = "min-pa";
+    const MIN_IP_OPTION: &str = "min-ip";
+    const ASCENDING_OPTION: &str = "ascending";
+    const DEFAULT_GROUP: &str = "hitting";
+
+    let get_minimum = |name: &str| options.get(name).map(|minimum| minimum.parse::<f64>()
+        .map_err(|_| QueryError::OptionError(name.to_string(), minimum.clone()))).transpose();
+    Ok(CustomBoard {
+        formula: parse_formula(formula)?,
+        name: formula.to_string(),
+        group: options.get(GROUP_OPTION).cloned().unwrap_or(DEFAULT_GROUP.to_string()),
+        min_plate_appearances: get_minimum(MIN_PA_OPTION)?,
+        min_innings: get_minimum(MIN_IP_OPTION)?,
+        ascending: options.contains_key(ASCENDING_OPTION)
+    })
+}
+
+// Aliases are checked first, then any category listed by the leagueLeaderTypes endpoint, and anything else is
+// read as a formula over the fields of the bulk season stats
+fn get_stats(category: &str, options: &HashMap<String, String>) -> Result<Stats, QueryError> {
+    const GROUP_OPTION: &str = "group";
+
+    let group = options.get(GROUP_OPTION);
+    match category {
+        "" | "all" => return Ok(Stats::All),
+        "b" => return Ok(Stats::Batting),
+        "p" => return Ok(Stats::Pitching),
+        "list" => return Ok(Stats::List),
+        _ => ()
+    }
+    if let Some((name, alias_group)) = CATEGORY_ALIASES.get(category) {
+        return Ok(Stats::Stat(name.to_string(), Some(group.cloned().unwrap_or(alias_group.to_string()))));
+    }
+    match get_leader_types()?.into_iter().find(|name| name.eq_ignore_ascii_case(category)) {
+        Some(name) => Ok(Stats::Stat(name, group.cloned())),
+        None => Ok(Stats::Custom(get_custom_board(category, options)?))
+    }
+}
+
+fn get_park_factor(split: &BulkSplit, league: Option<&LeagueConstants>, park_factors: &mut ParkFactors, season: i32) -> Option<f64> {
+    match (&split.team, league) {
+        (Some(team), Some(_)) => park_factors.get(&season.to_string(), team.id),
+        _ => None
+    }
+}
+
+// The sabermetric columns of the player and team views, without their leading label
+fn add_sabermetric_cells(row: &mut Row, split: &BulkSplit, group: &str, league: Option<&LeagueConstants>, park_factor: Option<f64>) {
+    let stat_values = get_stat_values(&split.stat);
+    let sabermetric_row = if group == "pitching" {
+        sabermetric_pitching_row("", &PitchingLine::from_values(&stat_values), league, park_factor)
+    }
+    else {
+        sabermetric_hitting_row("", &HittingLine::from_values(&stat_values), league, park_factor)
+    };
+    row.cells.extend(sabermetric_row.cells.into_iter().skip(1));
+}
+
+fn add_sabermetric_header(row: &mut Row, group: &str) {
+    let header = if group == "pitching" { sabermetric_pitching_header!("") } else { sabermetric_hitting_header!("") };
+    row.cells.extend(header.cells.into_iter().skip(1));
+}
+
+// Every player's bulk stats for the group, keyed by player id, since the leaders endpoint only has the ranked value
+fn get_player_splits(group: &str, filters: &LeaderFilters) -> Result<HashMap<i32, BulkSplit>, QueryError> {
+    let stat_type = filters.get_bulk_stat_type("sabermetrics")?;
+    let bulk_stats: BulkStats = get(bulk_stats_url!(stat_type, group, "ALL", filters.get_stats_params()))?.json()?;
+    Ok(bulk_stats.stats.into_iter().flat_map(|stat| stat.splits).map(|split| (split.player.id, split)).collect())
+}
+
+fn display_leader_stats(categories: &str, group: Option<&str>, limit: i32, filters: &LeaderFilters) -> Result<(), QueryError> {
     const COLS: usize = 4;
+    // Positions are filtered locally so a deeper pool is needed to fill the board
+    const POSITION_POOL_LIMIT: i32 = 500;
+
+    let pool_limit = if filters.position.is_some() { POSITION_POOL_LIMIT } else { limit };
+    let params = filters.get_params(group);
+    let url = match filters.team_id {
+        Some(team_id) => team_leaders_url!(team_id, categories, pool_limit, params),
+        None => leaders_url!(categories, pool_limit, params)
+    };
+    let leaders: Leaders = get(url)?.json()?;
 
-    let leaders: Leaders = get(leaders_url!(categories, group, limit))?.json()?;
+    let (league_constants, mut park_factors) = sabermetric_inputs(filters.show_sabermetrics);
+    let league = if filters.stat_type.is_none() { league_constants.get(&filters.season.to_string()) } else { None };
+    let mut player_splits: HashMap<String, HashMap<i32, BulkSplit>> = HashMap::new();
     for category in leaders.leagueLeaders {
-        let mut table = Table::new();
+        let stat_group = if category.statGroup.is_empty() { group.unwrap_or_default() } else { category.statGroup.as_str() };
+        let show_sabermetrics = filters.show_sabermetrics && !stat_group.is_empty();
+        if show_sabermetrics && !player_splits.contains_key(stat_group) {
+            player_splits.insert(stat_group.to_string(), get_player_splits(stat_group, filters)?);
+        }
 
-        table.add_row(row!(TableCell::builder(HEADER_MAP.get(category.leaderCategory.as_str())
-            .unwrap()).col_span(COLS).alignment(Alignment::Center).build()));
-        table.add_row(row!("Rank", "Player", "Team", "Stat"));
+        let mut header = row!("Rank", "Player", "Team", "Stat");
+        if show_sabermetrics {
+            add_sabermetric_header(&mut header, stat_group);
+        }
+        let mut table = Table::new();
+        table.add_row(row!(TableCell::builder(get_header(&category.leaderCategory))
+            .col_span(if show_sabermetrics { header.cells.len() } else { COLS }).alignment(Alignment::Center).build()));
+        table.add_row(header);
 
-        category.leaders.iter().for_each(|leader| table.add_row(
-            row!(leader.rank, &leader.person.fullName, &leader.team.name, &leader.value)));
+        for leader in category.leaders.iter().filter(|leader| filters.matches_position(&leader.person)).take(limit as usize) {
+            let mut row = row!(leader.rank, &leader.person.fullName, &leader.team.name, &leader.value);
+            if let Some(split) = player_splits.get(stat_group).and_then(|splits| splits.get(&leader.person.id)) {
+                let park_factor = get_park_factor(split, league, &mut park_factors, filters.season);
+                add_sabermetric_cells(&mut row, split, stat_group, league, park_factor);
+            }
+            table.add_row(row);
+        }
         println!("{}", table.render());
     }
     Ok(())
 }
 
-fn display_stat_leaders(stats: Stats, limit: i32) -> reqwest::Result<()> {
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 { format!("{value:.0}") } else { format!("{value:.3}") }
+}
+
+fn display_custom_leaders(board: &CustomBoard, limit: i32, filters: &LeaderFilters) -> Result<(), QueryError> {
+    const COLS: usize = 5;
+    const PLATE_APPEARANCES_FIELD: &str = "plateappearances";
+    const INNINGS_FIELD: &str = "inningspitched";
+
+    // Local minimums replace the official qualifier, so they need every player to filter from
+    let has_minimum = board.min_plate_appearances.is_some() || board.min_innings.is_some();
+    let player_pool = filters.player_pool.unwrap_or(if has_minimum { "ALL" } else { "QUALIFIED" });
+    let stat_type = filters.get_bulk_stat_type(&board.name)?;
+    let bulk_stats: BulkStats = get(bulk_stats_url!(stat_type, board.group, player_pool, filters.get_stats_params()))?.json()?;
+    let splits: Vec<&BulkSplit> = bulk_stats.stats.iter().flat_map(|stat| stat.splits.iter()).collect();
+
+    // Sabermetric stats are added as fields so formulas can use them, e.g. "woba" or "fip - era"
+    let uses_league = board.formula.get_stat_names().iter().any(|name| LEAGUE_RELATIVE_STATS.contains(&name.as_str()));
+    let (league_constants, mut park_factors) = sabermetric_inputs(uses_league || filters.show_sabermetrics);
+    let league = if filters.stat_type.is_none() { league_constants.get(&filters.season.to_string()) } else { None };
+    let values: Vec<HashMap<String, f64>> = splits.iter().map(|split| {
+        let mut stat_values = get_stat_values(&split.stat);
+        let park_factor = get_park_factor(split, league, &mut park_factors, filters.season);
+        let sabermetric_values: Vec<(&str, Option<f64>)> = if board.group == "pitching" {
+            get_pitching_values(&PitchingLine::from_values(&stat_values), league, park_factor).to_vec()
+        }
+        else {
+            get_hitting_values(&HittingLine::from_values(&stat_values), league, park_factor).to_vec()
+        };
+        stat_values.extend(sabermetric_values.into_iter().filter_map(|(name, value)| Some((name.to_string(), value?))));
+        stat_values
+    }).collect();
+    if let Some(unknown) = board.formula.get_stat_names().into_iter()
+        .find(|name| !values.is_empty() && values.iter().all(|stat_values| !stat_values.contains_key(name))) {
+        return Err(QueryError::EntryError(unknown));
+    }
+
+    let meets_minimum = |stat_values: &HashMap<String, f64>, field: &str, minimum: Option<f64>|
+        minimum.is_none_or(|minimum| stat_values.get(field).copied().unwrap_or(0.0) >= minimum);
+    let mut leaders: Vec<(&BulkSplit, f64, f64)> = splits.iter().zip(values.iter()).filter(|(split, stat_values)|
+        filters.matches_position(&split.player)
+            && meets_minimum(stat_values, PLATE_APPEARANCES_FIELD, board.min_plate_appearances)
+            && meets_minimum(stat_values, INNINGS_FIELD, board.min_innings))
+        .filter_map(|(split, stat_values)| {
+            let qualifier_field = if board.group == "pitching" { INNINGS_FIELD } else { PLATE_APPEARANCES_FIELD };
+            Some((*split, board.formula.evaluate(stat_values)?, stat_values.get(qualifier_field).copied().unwrap_or(0.0)))
+        })
+        .filter(|(_, value, _)| value.is_finite()).collect();
+    leaders.sort_by(|(_, value0, _), (_, value1, _)| if board.ascending { value0.total_cmp(value1) } else { value1.total_cmp(value0) });
+
+    let mut header = row!("Rank", "Player", "Team", if board.group == "pitching" { "IP" } else { "PA" }, "Value");
+    if filters.show_sabermetrics {
+        add_sabermetric_header(&mut header, &board.group);
+    }
+    let mut table = Table::new();
+    table.add_row(row!(TableCell::builder(format!("{} Leaders", board.name))
+        .col_span(if filters.show_sabermetrics { header.cells.len() } else { COLS }).alignment(Alignment::Center).build()));
+    table.add_row(header);
+
+    // Tied players share the rank of the first player with their value
+    let mut rank = 0;
+    for (i, (split, value, qualifier)) in leaders.iter().enumerate().take(limit as usize) {
+        if i == 0 || leaders[i - 1].1 != *value {
+            rank = i + 1;
+        }
+        let team = split.team.as_ref().map_or("", |team| team.name.as_str());
+        let mut row = row!(rank, &split.player.fullName, team, format_value(*qualifier), format_value(*value));
+        if filters.show_sabermetrics {
+            let park_factor = get_park_factor(split, league, &mut park_factors, filters.season);
+            add_sabermetric_cells(&mut row, split, &board.group, league, park_factor);
+        }
+        table.add_row(row);
+    }
+    println!("{}", table.render());
+    Ok(())
+}
+
+fn display_stat_leaders(stats: Stats, limit: i32, filters: &LeaderFilters) -> Result<(), QueryError> {
     match stats {
-        Stats::ALL => {
-            display_batting_leaders!(limit);
-            display_pitching_leaders!(limit);
+        Stats::All => {
+            display_batting_leaders!(limit, filters);
+            display_pitching_leaders!(limit, filters);
         },
-        Stats::Batting => display_batting_leaders!(limit),
-        Stats::Pitching => display_pitching_leaders!(limit),
-        Stats::Stat(category, group) => display_leader_stats(category, group, limit)?
+        Stats::Batting => display_batting_leaders!(limit, filters),
+        Stats::Pitching => display_pitching_leaders!(limit, filters),
+        Stats::Stat(category, group) => display_leader_stats(&category, group.as_deref(), limit, filters)?,
+        Stats::Custom(board) => display_custom_leaders(&board, limit, filters)?,
+        Stats::List => display_leader_types()?
     }
     Ok(())
 }
 
-pub(crate) fn display_leaders(query: &Vec<String>) -> Result<(), QueryError> {
+fn get_leader_filters(options: &HashMap<String, String>) -> Result<LeaderFilters, QueryError> {
+    const LEAGUE_OPTION: &str = "league";
+    const TEAM_OPTION: &str = "team";
+    const POSITION_OPTION: &str = "position";
+    const QUALIFIED_OPTION: &str = "qualified";
+    const ALL_OPTION: &str = "all";
+    const CAREER_OPTION: &str = "career";
+    const SINGLE_SEASON_OPTION: &str = "single-season";
+    const SABERMETRICS_OPTION: &str = "sabermetrics";
+    const AL_ID: i32 = 103;
+    const NL_ID: i32 = 104;
+
+    let league_id = match options.get(LEAGUE_OPTION).map(|league| league.to_ascii_lowercase()).as_deref() {
+        Some("al") => Some(AL_ID),
+        Some("nl") => Some(NL_ID),
+        Some(league) => return Err(QueryError::OptionError(LEAGUE_OPTION.to_string(), league.to_string())),
+        None => None
+    };
+    let team_id = match options.get(TEAM_OPTION) {
+        Some(abbreviation) => Some(get_team(&abbreviation.to_ascii_lowercase())?.1),
+        None => None
+    };
+    let player_pool = if options.contains_key(ALL_OPTION) { Some("ALL") }
+        else if options.contains_key(QUALIFIED_OPTION) { Some("QUALIFIED") }
+        else { None };
+    let stat_type = if options.contains_key(CAREER_OPTION) { Some("career") }
+        else if options.contains_key(SINGLE_SEASON_OPTION) { Some("statsSingleSeason") }
+        else { None };
+
+    Ok(LeaderFilters {
+        game_type: get_game_type(options)?,
+        season: get_season(options)?,
+        league_id,
+        team_id,
+        position: options.get(POSITION_OPTION).cloned(),
+        player_pool,
+        stat_type,
+        show_sabermetrics: options.contains_key(SABERMETRICS_OPTION)
+    })
+}
+
+pub(crate) fn display_leaders(query: &[String]) -> Result<(), QueryError> {
     const CATEGORY_INDEX: usize = 2;
     const LIMIT_INDEX: usize = 3;
     const DEFAULT_LIMIT: i32 = 8;
 
-    let stats= match get_query_param!(query, CATEGORY_INDEX, empty!()).as_str() {
-        "b" => Stats::Batting,
-        "p" => Stats::Pitching,
-        "avg" => Stats::Stat("battingAverage", "hitting"),
-        "hr" => Stats::Stat("homeRuns", "hitting"),
-        "rbi" => Stats::Stat("runsBattedIn", "hitting"),
-        "h" => Stats::Stat("hits", "hitting"),
-        "sb" => Stats::Stat("stolenBases", "hitting"),
-        "wins" => Stats::Stat("wins", "pitching"),
-        "era" => Stats::Stat("era", "pitching"),
-        "saves" => Stats::Stat("saves", "pitching"),
-        "so" => Stats::Stat("strikeOuts", "pitching"),
-        "whip" => Stats::Stat("whip", "pitching"),
-        _ => Stats::ALL
-    };
+    let (query, options) = split_options(query);
+    let filters = get_leader_filters(&options)?;
+    let stats = get_stats(&get_query_param!(query, CATEGORY_INDEX, empty!()), &options)?;
 
     let limit: i32 = if query.len() > LIMIT_INDEX {
         query[LIMIT_INDEX].parse::<i32>().unwrap_or(DEFAULT_LIMIT)
     }
     else { DEFAULT_LIMIT };
-    display_stat_leaders(stats, limit)?;
+    display_stat_leaders(stats, limit, &filters)?;
     Ok(())
 }
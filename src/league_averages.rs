@@ -1,15 +1,60 @@
-use regex::{Regex};
+use std::collections::HashMap;
 use term_table::row::{Row};
-use term_table::{row, Table, TableStyle};
-use term_table::table_cell::{TableCell};
-use crate::query::{empty, get_query_param, QueryError};
+use term_table::{Table, TableStyle};
+use crate::html_table::{parse_tables, Cell, HtmlTable};
+use crate::chart::line_chart;
+use crate::formula::parse_innings;
+use crate::hitting_stats::Batter;
+use crate::pitching_stats::Pitcher;
+use crate::query::{empty, get_query_param, get_season, split_options, terminal_width, QueryError};
+use crate::sabermetrics::ratio;
+use crate::teams::get_all_team_stats;
+
+// Every per team per game average on the batting and pitching pages, so averages are looked up without matching on headers
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub(crate) enum AverageStat {
+    Games, PlateAppearances, AtBats, Runs, Hits, Singles, Doubles, Triples, HomeRuns, RunsBattedIn, StolenBases, CaughtStealing,
+    Walks, Strikeouts, BattingAverage, OnBasePct, SluggingPct, Ops, TotalBases, GroundIntoDoublePlays, HitByPitch, SacBunts,
+    SacFlies, IntentionalWalks, BallsInPlay, Era, GamesFinished, CompleteGames, CompleteGameShutouts, Shutouts, Saves,
+    InningsPitched, EarnedRuns, Balks, WildPitches, BattersFaced, Whip, Babip, HitsPerNine, HomeRunsPerNine, WalksPerNine,
+    StrikeoutsPerNine, StrikeoutsPerWalk, Errors
+}
+
+// Each stat with its column header and the data-stat attribute baseball-reference tags its cells with
+type AverageColumn = (AverageStat, &'static str, &'static str);
+
+const BATTING_COLUMNS: [AverageColumn; 25] = [(AverageStat::Games, "G", "G"), (AverageStat::PlateAppearances, "PA", "PA"),
+    (AverageStat::AtBats, "AB", "AB"), (AverageStat::Runs, "R", "R"), (AverageStat::Hits, "H", "H"),
+    (AverageStat::Singles, "1B", "1B"), (AverageStat::Doubles, "2B", "2B"), (AverageStat::Triples, "3B", "3B"), (AverageStat::HomeRuns, "HR", "HR"),
+    (AverageStat::RunsBattedIn, "RBI", "RBI"), (AverageStat::StolenBases, "SB", "SB"), (AverageStat::CaughtStealing, "CS", "CS"),
+    (AverageStat::Walks, "BB", "BB"), (AverageStat::Strikeouts, "SO", "SO"), (AverageStat::BattingAverage, "BA", "batting_avg"),
+    (AverageStat::OnBasePct, "OBP", "onbase_perc"), (AverageStat::SluggingPct, "SLG", "slugging_perc"),
+    (AverageStat::Ops, "OPS", "onbase_plus_slugging"), (AverageStat::TotalBases, "TB", "TB"),
+    (AverageStat::GroundIntoDoublePlays, "GDP", "GIDP"), (AverageStat::HitByPitch, "HBP", "HBP"), (AverageStat::SacBunts, "SH", "SH"),
+    (AverageStat::SacFlies, "SF", "SF"), (AverageStat::IntentionalWalks, "IBB", "IBB"), (AverageStat::BallsInPlay, "BIP", "BIP")];
+
+const PITCHING_COLUMNS: [AverageColumn; 27] = [(AverageStat::Era, "ERA", "earned_run_avg"), (AverageStat::Games, "G", "G"),
+    (AverageStat::GamesFinished, "GF", "GF"), (AverageStat::CompleteGames, "CG", "CG"),
+    (AverageStat::CompleteGameShutouts, "SHO", "SHO_cg"), (AverageStat::Shutouts, "tSHO", "SHO"), (AverageStat::Saves, "SV", "SV"),
+    (AverageStat::InningsPitched, "IP", "IP"), (AverageStat::Hits, "H", "H"), (AverageStat::Runs, "R", "R"),
+    (AverageStat::EarnedRuns, "ER", "ER"), (AverageStat::HomeRuns, "HR", "HR"), (AverageStat::Walks, "BB", "BB"),
+    (AverageStat::IntentionalWalks, "IBB", "IBB"), (AverageStat::Strikeouts, "SO", "SO"), (AverageStat::HitByPitch, "HBP", "HBP"),
+    (AverageStat::Balks, "BK", "BK"), (AverageStat::WildPitches, "WP", "WP"), (AverageStat::BattersFaced, "BF", "batters_faced"),
+    (AverageStat::Whip, "WHIP", "whip"), (AverageStat::Babip, "BAbip", "batting_avg_bip"),
+    (AverageStat::HitsPerNine, "H9", "hits_per_nine"), (AverageStat::HomeRunsPerNine, "HR9", "home_runs_per_nine"),
+    (AverageStat::WalksPerNine, "BB9", "bases_on_balls_per_nine"), (AverageStat::StrikeoutsPerNine, "SO9", "strikeouts_per_nine"),
+    (AverageStat::StrikeoutsPerWalk, "SO/W", "strikeouts_per_base_on_balls"), (AverageStat::Errors, "E", "E")];
+
+const YEAR_COLUMN: (&str, &str) = ("Year", "year_ID");
+
+const ERAS: [(&str, i32, i32); 6] = [("Dead Ball", 1901, 1919), ("Live Ball", 1920, 1960), ("Expansion", 1961, 1993),
+    ("Steroid", 1994, 2005), ("Post-Steroid", 2006, 2014), ("Statcast", 2015, i32::MAX)];
 
 macro_rules! batting_url {
     () => {
         ("https://www.baseball-reference.com/leagues/majors/bat.shtml",
         "Batting Stat Averages Per Team Per Game",
-        row!("Year", "G", "PA", "AB", "R", "H", "1B", "2B", "3B", "HR", "RBI", "SB", "CS", "BB",
-        "SO", "BA", "OBP", "SLG", "OPS", "TB", "GDP", "HBP", "SH", "SF", "IBB", "BIP"))
+        &BATTING_COLUMNS[..])
     };
 }
 
@@ -17,47 +62,340 @@ macro_rules! pitching_url {
     () => {
         ("https://www.baseball-reference.com/leagues/majors/pitch.shtml",
         "Pitching Stat Averages Per Team Per Game",
-        row!("Year", "ERA", "G", "GF", "CG", "SHO", "tSHO", "SV", "IP", "H", "R", "ER", "HR",
-        "BB", "IBB", "SO", "HBP", "BK", "WP", "BF", "WHIP", "BAbip", "H9", "HR9", "BB9", "SO9", "SO/W", "E"))
+        &PITCHING_COLUMNS[..])
     };
 }
 
-pub(crate) fn display_league_averages(query: &Vec<String>, is_batting: bool) -> Result<(), QueryError> {
-    const ALL_TIME_INDEX: usize = 2;
-    const COL_OFFSET: usize = 4;
+// One season of per team per game averages. The values are parsed once, the cells keep the page's text for display
+#[derive(Clone)]
+pub(crate) struct LeagueSeasonAverages {
+    pub(crate) year: String,
+    values: HashMap<AverageStat, f64>,
+    cols: Vec<String>
+}
+
+impl LeagueSeasonAverages {
+    // Averages computed here rather than scraped are formatted like the page's cells
+    fn from_values(year: String, columns: &[AverageColumn], values: HashMap<AverageStat, f64>) -> LeagueSeasonAverages {
+        let cols = columns.iter().map(|(stat, _, _)| format_average(*stat, values.get(stat).copied())).collect();
+        LeagueSeasonAverages { year, values, cols }
+    }
+
+    pub(crate) fn get(&self, stat: AverageStat) -> Option<f64> {
+        self.values.get(&stat).copied()
+    }
 
-    let (url, header, stat_header) = if is_batting { batting_url!() }
+    fn get_row(&self, indices: &[usize]) -> Row {
+        Row::new(std::iter::once(&self.year).chain(indices.iter().map(|i| &self.cols[*i])))
+    }
+}
+
+// A column is found by its data-stat attribute, falling back to its header text if the attribute was renamed
+fn get_column_index(header_row: &[Cell], (header, data_stat): (&str, &str)) -> Option<usize> {
+    header_row.iter().position(|cell| cell.data_stat == data_stat)
+        .or_else(|| header_row.iter().position(|cell| cell.text == header))
+}
+
+// The averages table is the first with a year column, its header is the first row with one as grouping rows may come before it
+fn get_averages_table(tables: Vec<HtmlTable>) -> Option<(HtmlTable, usize)> {
+    tables.into_iter().find_map(|table| {
+        let header_index = table.iter().position(|row| get_column_index(row, YEAR_COLUMN).is_some())?;
+        Some((table, header_index))
+    })
+}
+
+// Year-by-year averages, most recent season first. Repeated header rows and rows without a year are skipped
+fn parse_league_averages(html: &str, columns: &[AverageColumn]) -> Option<Vec<LeagueSeasonAverages>> {
+    let (table, header_index) = get_averages_table(parse_tables(html))?;
+    let header_row = &table[header_index];
+    let year_index = get_column_index(header_row, YEAR_COLUMN)?;
+    let indices: Vec<Option<usize>> = columns.iter()
+        .map(|(_, header, data_stat)| get_column_index(header_row, (header, data_stat))).collect();
+
+    Some(table.iter().skip(header_index + 1).filter_map(|row| {
+        let year = &row.get(year_index)?.text;
+        if year.parse::<i32>().is_err() {
+            return None;
+        }
+        let cols: Vec<String> = indices.iter()
+            .map(|index| index.and_then(|i| row.get(i)).map_or("".to_string(), |cell| cell.text.clone())).collect();
+        Some(LeagueSeasonAverages {
+            year: year.clone(),
+            values: columns.iter().zip(cols.iter())
+                .filter_map(|((stat, _, _), col)| Some((*stat, col.parse::<f64>().ok()?))).collect(),
+            cols
+        })
+    }).collect())
+}
+
+pub(crate) fn parse_averages_page(html: &str, is_batting: bool) -> Option<Vec<LeagueSeasonAverages>> {
+    let (_, _, columns) = if is_batting { batting_url!() }
     else { pitching_url!() };
 
-    let all_time = get_query_param!(query, ALL_TIME_INDEX, empty!());
-    let row_count = if all_time == "a" || all_time == "all-time" { -1 } else { 1 };
+    parse_league_averages(html, columns)
+}
+
+pub(crate) fn get_league_averages(is_batting: bool) -> Result<Vec<LeagueSeasonAverages>, QueryError> {
+    let (url, _, _) = if is_batting { batting_url!() }
+    else { pitching_url!() };
+
+    let html = reqwest::blocking::get(url)?.text()?;
+    parse_averages_page(&html, is_batting).ok_or(QueryError::EntryError(url.to_string()))
+}
+
+fn sum<T>(items: &[T], stat: impl Fn(&T) -> i32) -> f64 {
+    items.iter().map(stat).sum::<i32>() as f64
+}
+
+fn get_batting_values(batters: &[&Batter]) -> HashMap<AverageStat, Option<f64>> {
+    let games = sum(batters, |batter| batter.gamesPlayed);
+    let per_game = |stat: fn(&&Batter) -> i32| ratio(sum(batters, stat), games);
+    let at_bats = sum(batters, |batter| batter.atBats);
+    let hits = sum(batters, |batter| batter.hits);
+    let times_on_base = hits + sum(batters, |batter| batter.baseOnBalls + batter.hitByPitch);
+    let obp = ratio(times_on_base, at_bats + sum(batters, |batter| batter.baseOnBalls + batter.hitByPitch + batter.sacFlies));
+    let slg = ratio(sum(batters, |batter| batter.totalBases), at_bats);
+    HashMap::from([
+        (AverageStat::Games, ratio(games, batters.len() as f64)),
+        (AverageStat::PlateAppearances, per_game(|batter| batter.plateAppearances)),
+        (AverageStat::AtBats, per_game(|batter| batter.atBats)),
+        (AverageStat::Runs, per_game(|batter| batter.runs)),
+        (AverageStat::Hits, per_game(|batter| batter.hits)),
+        (AverageStat::Singles, per_game(|batter| batter.hits - batter.doubles - batter.triples - batter.homeRuns)),
+        (AverageStat::Doubles, per_game(|batter| batter.doubles)),
+        (AverageStat::Triples, per_game(|batter| batter.triples)),
+        (AverageStat::HomeRuns, per_game(|batter| batter.homeRuns)),
+        (AverageStat::RunsBattedIn, per_game(|batter| batter.rbi)),
+        (AverageStat::StolenBases, per_game(|batter| batter.stolenBases)),
+        (AverageStat::CaughtStealing, per_game(|batter| batter.caughtStealing)),
+        (AverageStat::Walks, per_game(|batter| batter.baseOnBalls)),
+        (AverageStat::Strikeouts, per_game(|batter| batter.strikeOuts)),
+        (AverageStat::BattingAverage, ratio(hits, at_bats)),
+        (AverageStat::OnBasePct, obp),
+        (AverageStat::SluggingPct, slg),
+        (AverageStat::Ops, obp.zip(slg).map(|(obp, slg)| obp + slg)),
+        (AverageStat::TotalBases, per_game(|batter| batter.totalBases)),
+        (AverageStat::GroundIntoDoublePlays, per_game(|batter| batter.groundIntoDoublePlay)),
+        (AverageStat::HitByPitch, per_game(|batter| batter.hitByPitch)),
+        (AverageStat::SacBunts, per_game(|batter| batter.sacBunts)),
+        (AverageStat::SacFlies, per_game(|batter| batter.sacFlies)),
+        (AverageStat::IntentionalWalks, per_game(|batter| batter.intentionalWalks)),
+        (AverageStat::BallsInPlay, per_game(|batter| batter.atBats - batter.strikeOuts - batter.homeRuns + batter.sacFlies))
+    ])
+}
+
+// Complete game shutouts and errors aren't in team pitching stats so those columns stay empty
+fn get_pitching_values(pitchers: &[&Pitcher]) -> HashMap<AverageStat, Option<f64>> {
+    let games = sum(pitchers, |pitcher| pitcher.gamesPlayed);
+    let per_game = |stat: fn(&&Pitcher) -> i32| ratio(sum(pitchers, stat), games);
+    let innings: f64 = pitchers.iter().filter_map(|pitcher| parse_innings(&pitcher.inningsPitched)).sum();
+    let per_nine = |stat: fn(&&Pitcher) -> i32| ratio(9.0 * sum(pitchers, stat), innings);
+    let hits = sum(pitchers, |pitcher| pitcher.hits);
+    let home_runs = sum(pitchers, |pitcher| pitcher.homeRuns);
+    let walks = sum(pitchers, |pitcher| pitcher.baseOnBalls);
+    let strikeouts = sum(pitchers, |pitcher| pitcher.strikeOuts);
+    HashMap::from([
+        (AverageStat::Era, per_nine(|pitcher| pitcher.earnedRuns)),
+        (AverageStat::Games, ratio(games, pitchers.len() as f64)),
+        (AverageStat::GamesFinished, per_game(|pitcher| pitcher.gamesFinished)),
+        (AverageStat::CompleteGames, per_game(|pitcher| pitcher.completeGames)),
+        (AverageStat::Shutouts, per_game(|pitcher| pitcher.shutouts)),
+        (AverageStat::Saves, per_game(|pitcher| pitcher.saves)),
+        (AverageStat::InningsPitched, ratio(innings, games)),
+        (AverageStat::Hits, per_game(|pitcher| pitcher.hits)),
+        (AverageStat::Runs, per_game(|pitcher| pitcher.runs)),
+        (AverageStat::EarnedRuns, per_game(|pitcher| pitcher.earnedRuns)),
+        (AverageStat::HomeRuns, per_game(|pitcher| pitcher.homeRuns)),
+        (AverageStat::Walks, per_game(|pitcher| pitcher.baseOnBalls)),
+        (AverageStat::IntentionalWalks, per_game(|pitcher| pitcher.intentionalWalks)),
+        (AverageStat::Strikeouts, per_game(|pitcher| pitcher.strikeOuts)),
+        (AverageStat::HitByPitch, per_game(|pitcher| pitcher.hitByPitch)),
+        (AverageStat::Balks, per_game(|pitcher| pitcher.balks)),
+        (AverageStat::WildPitches, per_game(|pitcher| pitcher.wildPitches)),
+        (AverageStat::BattersFaced, per_game(|pitcher| pitcher.battersFaced)),
+        (AverageStat::Whip, ratio(walks + hits, innings)),
+        (AverageStat::Babip, ratio(hits - home_runs, sum(pitchers, |pitcher| pitcher.atBats - pitcher.strikeOuts - pitcher.homeRuns + pitcher.sacFlies))),
+        (AverageStat::HitsPerNine, per_nine(|pitcher| pitcher.hits)),
+        (AverageStat::HomeRunsPerNine, per_nine(|pitcher| pitcher.homeRuns)),
+        (AverageStat::WalksPerNine, per_nine(|pitcher| pitcher.baseOnBalls)),
+        (AverageStat::StrikeoutsPerNine, per_nine(|pitcher| pitcher.strikeOuts)),
+        (AverageStat::StrikeoutsPerWalk, ratio(strikeouts, walks))
+    ])
+}
+
+fn format_average(stat: AverageStat, value: Option<f64>) -> String {
+    const RATE_STATS: [AverageStat; 5] = [AverageStat::BattingAverage, AverageStat::OnBasePct, AverageStat::SluggingPct,
+        AverageStat::Ops, AverageStat::Babip];
+
+    match value {
+        Some(value) if stat == AverageStat::Games => format!("{value:.0}"),
+        Some(value) if RATE_STATS.contains(&stat) => {
+            let rate = format!("{value:.3}");
+            if value < 1.0 { rate.replacen("0.", ".", 1) } else { rate }
+        },
+        Some(value) => format!("{value:.2}"),
+        None => "".to_string()
+    }
+}
+
+// Per team per game averages computed from the season totals of every team
+pub(crate) fn get_statsapi_averages(season: i32, is_batting: bool) -> Result<LeagueSeasonAverages, QueryError> {
+    let (_, _, columns) = if is_batting { batting_url!() }
+    else { pitching_url!() };
+
+    let (hitting, pitching) = get_all_team_stats(season)?;
+    let values = if is_batting { get_batting_values(&hitting.iter().map(|(_, stat)| stat).collect::<Vec<&Batter>>()) }
+    else { get_pitching_values(&pitching.iter().map(|(_, stat)| stat).collect::<Vec<&Pitcher>>()) };
+    let values = values.into_iter().filter_map(|(stat, value)| Some((stat, value?))).collect();
+    Ok(LeagueSeasonAverages::from_values(season.to_string(), columns, values))
+}
+
+fn display_source_comparison(table: &mut Table, baseball_reference: Option<LeagueSeasonAverages>, statsapi: LeagueSeasonAverages,
+    columns: &[AverageColumn], indices: &[usize]) {
+    let diff: Vec<String> = columns.iter().map(|(stat, _, _)| {
+        match (baseball_reference.as_ref().and_then(|averages| averages.get(*stat)), statsapi.get(*stat)) {
+            (Some(baseball_reference), Some(statsapi)) => format!("{:+.3}", statsapi - baseball_reference),
+            _ => "".to_string()
+        }
+    }).collect();
+
+    let get_row = |label: &str, cols: &[String]| Row::new(std::iter::once(label).chain(indices.iter().map(|i| cols[*i].as_str())));
+    if let Some(baseball_reference) = &baseball_reference {
+        table.add_row(get_row("B-Ref", &baseball_reference.cols));
+    }
+    table.add_row(get_row("StatsAPI", &statsapi.cols));
+    table.add_row(get_row("Diff", &diff));
+}
 
-    let stats = reqwest::blocking::get(url)?.text()?;
+fn get_year_option(options: &HashMap<String, String>, name: &str) -> Result<Option<i32>, QueryError> {
+    options.get(name).map(|year| year.parse::<i32>().map_err(|_| QueryError::OptionError(name.to_string(), year.clone()))).transpose()
+}
+
+fn get_column_indices(columns: &[AverageColumn], options: &HashMap<String, String>) -> Result<Vec<usize>, QueryError> {
+    const COLS_OPTION: &str = "cols";
+
+    match options.get(COLS_OPTION) {
+        Some(cols) => cols.split(',').map(|col| {
+            columns.iter().position(|(_, header, _)| header.eq_ignore_ascii_case(col.trim()))
+                .ok_or(QueryError::OptionError(COLS_OPTION.to_string(), col.to_string()))
+        }).collect(),
+        None => Ok((0..columns.len()).collect())
+    }
+}
+
+// Each era's averages are the mean of its seasons' per game averages
+fn get_era_averages(seasons: &[LeagueSeasonAverages], columns: &[AverageColumn]) -> Vec<LeagueSeasonAverages> {
+    ERAS.iter().filter_map(|(name, start, end)| {
+        let era: Vec<&LeagueSeasonAverages> = seasons.iter()
+            .filter(|averages| averages.year.parse::<i32>().is_ok_and(|year| year >= *start && year <= *end)).collect();
+        if era.is_empty() {
+            return None;
+        }
+        let years = if *end == i32::MAX { format!("{start}-") } else { format!("{start}-{end}") };
+        let values = columns.iter().filter_map(|(stat, _, _)| {
+            let values: Vec<f64> = era.iter().filter_map(|averages| averages.get(*stat)).collect();
+            Some((*stat, ratio(values.iter().sum(), values.len() as f64)?))
+        }).collect();
+        Some(LeagueSeasonAverages::from_values(format!("{name} ({years})"), columns, values))
+    }).collect()
+}
+
+// A single season's averages, or the mean over an era when given an era name like "statcast" or "dead-ball"
+pub(crate) fn get_target_averages(seasons: &[LeagueSeasonAverages], target: &str, is_batting: bool) -> Option<LeagueSeasonAverages> {
+    let (_, _, columns) = if is_batting { batting_url!() }
+    else { pitching_url!() };
+    let normalize = |name: &str| name.to_ascii_lowercase().replace(|char: char| !char.is_ascii_alphanumeric(), "");
+
+    if target.parse::<i32>().is_ok() {
+        return seasons.iter().find(|averages| averages.year == target).cloned();
+    }
+    get_era_averages(seasons, columns).into_iter().find(|era| {
+        let name = era.year.split(" (").next().unwrap_or_default();
+        normalize(name) == normalize(target)
+    })
+}
 
-    let table_regex = Regex::new(r"<tbody>[\S\s]*?League Year-By-Year").unwrap();
-    let row_regex = Regex::new(r"<tr >(<th.*?</th>).*?</tr>").unwrap();
-    let col_regex = Regex::new(r">([\d.]*)</(td|a)>").unwrap();
+fn display_averages_chart(seasons: &[LeagueSeasonAverages], columns: &[AverageColumn], col: &str) -> Result<(), QueryError> {
+    const CHART_OPTION: &str = "chart";
+    const CHART_HEIGHT: usize = 16;
+
+    let (stat, header, _) = columns.iter().find(|(_, header, _)| header.eq_ignore_ascii_case(col))
+        .ok_or(QueryError::OptionError(CHART_OPTION.to_string(), col.to_string()))?;
+    let points: Vec<(&String, f64)> = seasons.iter().rev().filter_map(|averages| Some((&averages.year, averages.get(*stat)?))).collect();
+    let (Some((first, _)), Some((last, _))) = (points.first(), points.last()) else {
+        return Err(QueryError::OptionError(CHART_OPTION.to_string(), col.to_string()));
+    };
+
+    let values: Vec<f64> = points.iter().map(|(_, value)| *value).collect();
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    println!("\n{} Per Team Per Game, {} to {}\n", header, first, last);
+    println!("{}", line_chart(&values, min, max, CHART_HEIGHT, terminal_width(), |value| format!("{value:.2}")));
+    Ok(())
+}
+
+pub(crate) fn display_league_averages(query: &[String], is_batting: bool) -> Result<(), QueryError> {
+    const ALL_TIME_INDEX: usize = 2;
+    const SOURCE_OPTION: &str = "source";
+    const SEASON_OPTION: &str = "season";
+    const FROM_OPTION: &str = "from";
+    const TO_OPTION: &str = "to";
+    const CHART_OPTION: &str = "chart";
+    const ERAS_OPTION: &str = "eras";
+
+    let (query, options) = split_options(query);
+    let (_, header, columns) = if is_batting { batting_url!() }
+    else { pitching_url!() };
+    let indices = get_column_indices(columns, &options)?;
+    let stat_header = Row::new(std::iter::once(YEAR_COLUMN.0).chain(indices.iter().map(|i| columns[*i].1)));
+
+    let from = get_year_option(&options, FROM_OPTION)?;
+    let to = get_year_option(&options, TO_OPTION)?;
+    let all_time = get_query_param!(query, ALL_TIME_INDEX, empty!());
+    let row_count = if all_time == "a" || all_time == "all-time" || from.is_some() || to.is_some() { -1 } else { 1 };
 
     let mut table = Table::new();
     table.style = TableStyle::thin();
     table.add_row(stat_header.clone());
-    let mut rows = 0;
 
-    for row in row_regex.captures_iter(table_regex.find(stats.as_str()).unwrap().as_str()) {
-        let mut cols = vec![];
-        let mut i = 0;
+    let source = options.get(SOURCE_OPTION).map(|source| source.to_ascii_lowercase());
+    // StatsAPI averages are a single season so the year range, chart and era views only work with baseball-reference
+    if let Some(source @ ("statsapi" | "compare")) = source.as_deref() {
+        if let Some(option) = [FROM_OPTION, TO_OPTION, CHART_OPTION, ERAS_OPTION].into_iter().find(|option| options.contains_key(*option)) {
+            return Err(QueryError::OptionError(SOURCE_OPTION.to_string(), format!("{source} (not available with --{option})")));
+        }
+    }
+
+    match source.as_deref() {
+        Some("statsapi") => table.add_row(get_statsapi_averages(get_season(&options)?, is_batting)?.get_row(&indices)),
+        Some("compare") => {
+            let season = get_season(&options)?;
+            let statsapi = get_statsapi_averages(season, is_batting)?;
+            let baseball_reference = get_league_averages(is_batting)?.into_iter().find(|averages| averages.year == season.to_string());
+            display_source_comparison(&mut table, baseball_reference, statsapi, columns, &indices);
+        },
+        Some("bref") | None => {
+            let season = options.get(SEASON_OPTION);
+            let seasons: Vec<LeagueSeasonAverages> = get_league_averages(is_batting)?.into_iter().filter(|averages| {
+                let Ok(year) = averages.year.parse::<i32>() else { return false };
+                season.is_none_or(|season| averages.year == *season)
+                    && from.is_none_or(|from| year >= from) && to.is_none_or(|to| year <= to)
+            }).collect();
 
-        for col in col_regex.captures_iter(&row[0]) {
-            if i == 0 || i > COL_OFFSET {
-                cols.push(col[1].to_string());
+            if let Some(col) = options.get(CHART_OPTION) {
+                return display_averages_chart(&seasons, columns, col);
             }
-            i += 1;
-        }
+            let seasons = if options.contains_key(ERAS_OPTION) { get_era_averages(&seasons, columns) } else { seasons };
 
-        table.add_row(Row::new(cols));
-        rows += 1;
-        if rows == row_count { break };
-        if rows & 7 == 0 { table.add_row(stat_header.clone()) }
+            let mut rows = 0;
+            for averages in &seasons {
+                table.add_row(averages.get_row(&indices));
+                rows += 1;
+                if rows == row_count && !options.contains_key(ERAS_OPTION) { break };
+                if rows & 7 == 0 { table.add_row(stat_header.clone()) }
+            }
+        },
+        Some(source) => return Err(QueryError::OptionError(SOURCE_OPTION.to_string(), source.to_string()))
     }
 
     println!("\n{}\n{}", header, table.render()
@@ -75,4 +413,67 @@ pub(crate) fn display_league_averages(query: &Vec<String>, is_batting: bool) ->
         .replace("─┤", "┤")
     );
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batting() -> Vec<LeagueSeasonAverages> {
+        parse_league_averages(include_str!("../tests/fixtures/bat.shtml"), &BATTING_COLUMNS).unwrap()
+    }
+
+    fn pitching() -> Vec<LeagueSeasonAverages> {
+        parse_league_averages(include_str!("../tests/fixtures/pitch.shtml"), &PITCHING_COLUMNS).unwrap()
+    }
+
+    #[test]
+    fn repeated_headers_and_spacers_are_skipped() {
+        let seasons = batting();
+        let years: Vec<&str> = seasons.iter().map(|averages| averages.year.as_str()).collect();
+        assert_eq!(years, ["2024", "2023", "2022", "2021"]);
+    }
+
+    #[test]
+    fn values_are_parsed_by_data_stat() {
+        let season = &batting()[0];
+        assert_eq!(season.get(AverageStat::Games), Some(162.0));
+        assert_eq!(season.get(AverageStat::Runs), Some(4.39));
+        assert_eq!(season.get(AverageStat::BattingAverage), Some(0.243));
+        assert_eq!(season.cols[14], ".243");
+    }
+
+    #[test]
+    fn renamed_data_stat_falls_back_to_the_header() {
+        assert_eq!(batting()[1].get(AverageStat::Ops), Some(0.734));
+        assert_eq!(pitching()[0].get(AverageStat::Babip), Some(0.291));
+    }
+
+    #[test]
+    fn missing_columns_are_empty() {
+        let season = &batting()[0];
+        assert_eq!(season.get(AverageStat::BallsInPlay), None);
+        assert_eq!(season.cols[24], "");
+        assert_eq!(pitching()[0].get(AverageStat::Errors), None);
+    }
+
+    #[test]
+    fn year_column_is_found_by_its_header() {
+        let seasons = pitching();
+        assert_eq!(seasons.iter().map(|averages| averages.year.as_str()).collect::<Vec<&str>>(), ["2024", "2023"]);
+        assert_eq!(seasons[0].get(AverageStat::Era), Some(4.08));
+    }
+
+    #[test]
+    fn pages_without_a_year_column_have_no_averages() {
+        let html = "<table><tr><th data-stat=\"team\">Team</th></tr><tr><td data-stat=\"team\">NYY</td></tr></table>";
+        assert!(parse_league_averages(html, &BATTING_COLUMNS).is_none());
+    }
+
+    #[test]
+    fn era_averages_are_the_mean_of_their_seasons() {
+        let eras = get_era_averages(&batting(), &BATTING_COLUMNS);
+        let statcast = eras.iter().find(|era| era.year.starts_with("Statcast")).unwrap();
+        assert!((statcast.get(AverageStat::Runs).unwrap() - (4.39 + 4.62 + 4.28 + 4.53) / 4.0).abs() < 1e-9);
+        assert_eq!(statcast.cols[3], "4.46");
+    }
+}
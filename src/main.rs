@@ -9,14 +9,28 @@ mod game;
 mod leaders;
 mod query;
 mod league_averages;
+mod bracket;
+mod calendar;
+mod chart;
+mod scoreboard;
+mod simulation;
+mod formula;
+mod sabermetrics;
+mod parks;
+mod html_table;
+mod comparison;
 
 use std::{env};
+use crate::bracket::display_bracket;
+use crate::comparison::compare_query;
 use crate::database::update_database;
 use crate::standings::display_standings;
-use crate::game::{games_query, season_games_query};
+use crate::game::{games_query, lineup_query, season_games_query};
 use crate::leaders::display_leaders;
+use crate::parks::display_parks;
 use crate::league_averages::display_league_averages;
 use crate::query::{empty, get_query_param};
+use crate::scoreboard::scoreboard_query;
 use crate::stats::{stats_query};
 use crate::teams::display_team_stats;
 
@@ -26,15 +40,20 @@ fn main() {
     let query: Vec<String> = env::args().collect();
     let res= match get_query_param!(&query, QUERY_TYPE_INDEX, empty!()).as_str() {
         "g" | "games" => games_query(&query),
+        "lu" | "lineup" => lineup_query(&query),
+        "sb" | "scoreboard" => scoreboard_query(&query),
         "r" | "results" => season_games_query(&query),
         "u" | "schedule" => season_games_query(&query),
         "s" | "stats" => stats_query(&query),
+        "c" | "compare" => compare_query(&query),
         "t" | "teams" => display_team_stats(&query),
         "l" | "leaders" => display_leaders(&query),
         "b" | "league-batting-stats" => display_league_averages(&query, true),
         "p" | "league-pitching-stats" => display_league_averages(&query, false),
+        "bracket" => display_bracket(&query),
+        "parks" => display_parks(&query),
         "update" => update_database(&query),
-        _ => display_standings()
+        _ => display_standings(&query)
     };
 
     match res {
@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+use reqwest::blocking::get;
+use serde::Deserialize;
+use term_table::{row, Table, TableStyle};
+use term_table::row::Row;
+use term_table::table_cell::TableCell;
+use crate::query::{get_season, split_options, QueryError};
+
+#[derive(Deserialize)]
+struct TeamSplitStats {
+    stats: Vec<SplitGroup>
+}
+
+#[derive(Deserialize)]
+struct SplitGroup {
+    group: Group,
+    splits: Vec<TeamSplit>
+}
+
+#[derive(Deserialize)]
+struct Group {
+    displayName: String
+}
+
+#[derive(Deserialize)]
+struct TeamSplit {
+    team: Team,
+    #[serde(default)]
+    isHome: bool,
+    stat: SplitStat
+}
+
+#[derive(Deserialize)]
+struct Team {
+    id: i32
+}
+
+#[derive(Deserialize)]
+struct SplitStat {
+    #[serde(default)]
+    gamesPlayed: i32,
+    #[serde(default)]
+    runs: i32,
+    #[serde(default)]
+    homeRuns: i32
+}
+
+#[derive(Deserialize)]
+struct Teams {
+    teams: Vec<TeamInfo>
+}
+
+#[derive(Deserialize)]
+struct TeamInfo {
+    id: i32,
+    name: String,
+    venue: VenueName
+}
+
+#[derive(Deserialize)]
+struct VenueName {
+    id: i32
+}
+
+#[derive(Deserialize)]
+struct Venues {
+    venues: Vec<Venue>
+}
+
+#[derive(Deserialize)]
+struct Venue {
+    id: i32,
+    name: String,
+    fieldInfo: Option<FieldInfo>
+}
+
+#[derive(Deserialize)]
+struct FieldInfo {
+    capacity: Option<i32>,
+    roofType: Option<String>,
+    leftLine: Option<i32>,
+    leftCenter: Option<i32>,
+    center: Option<i32>,
+    rightCenter: Option<i32>,
+    rightLine: Option<i32>
+}
+
+// Runs and home runs scored by both teams per game at home and on the road
+#[derive(Default)]
+pub(crate) struct ParkSplits {
+    home_games: i32,
+    home_game_runs: i32,
+    home_home_runs: i32,
+    away_games: i32,
+    away_game_runs: i32,
+    away_home_runs: i32
+}
+
+impl ParkSplits {
+    fn per_game(count: i32, games: i32) -> Option<f64> {
+        if games == 0 { None } else { Some(count as f64 / games as f64) }
+    }
+
+    fn factor(home: Option<f64>, away: Option<f64>) -> Option<f64> {
+        match (home, away) {
+            (Some(home), Some(away)) if away > 0.0 => Some(home / away),
+            _ => None
+        }
+    }
+
+    // 1.0 is neutral, above favours hitters
+    pub(crate) fn run_factor(&self) -> Option<f64> {
+        ParkSplits::factor(ParkSplits::per_game(self.home_game_runs, self.home_games),
+            ParkSplits::per_game(self.away_game_runs, self.away_games))
+    }
+
+    fn home_run_factor(&self) -> Option<f64> {
+        ParkSplits::factor(ParkSplits::per_game(self.home_home_runs, self.home_games),
+            ParkSplits::per_game(self.away_home_runs, self.away_games))
+    }
+}
+
+macro_rules! team_splits_url {
+    ($season:expr) => {
+        format!("https://statsapi.mlb.com/api/v1/teams/stats?stats=homeAndAway&group=hitting,pitching&sportIds=1&season={}", $season)
+    };
+}
+
+macro_rules! teams_url {
+    ($season:expr) => {
+        format!("https://statsapi.mlb.com/api/v1/teams?sportId=1&season={}", $season)
+    };
+}
+
+macro_rules! venues_url {
+    ($venue_ids:expr, $season:expr) => {
+        format!("https://statsapi.mlb.com/api/v1/venues?venueIds={}&hydrate=fieldInfo&season={}", $venue_ids, $season)
+    };
+}
+
+// Hitting splits hold the runs a team scored and pitching splits the runs it allowed, together everything scored in its games
+pub(crate) fn get_park_splits(season: i32) -> reqwest::Result<HashMap<i32, ParkSplits>> {
+    let team_stats: TeamSplitStats = get(team_splits_url!(season))?.json()?;
+
+    let mut park_splits: HashMap<i32, ParkSplits> = HashMap::new();
+    for group in &team_stats.stats {
+        let is_hitting = group.group.displayName == "hitting";
+        for split in &group.splits {
+            let splits = park_splits.entry(split.team.id).or_default();
+            let stat = &split.stat;
+            match (split.isHome, is_hitting) {
+                (true, true) => {
+                    splits.home_games = stat.gamesPlayed;
+                    splits.home_game_runs += stat.runs;
+                    splits.home_home_runs += stat.homeRuns;
+                },
+                (true, false) => {
+                    splits.home_game_runs += stat.runs;
+                    splits.home_home_runs += stat.homeRuns;
+                },
+                (false, true) => {
+                    splits.away_games = stat.gamesPlayed;
+                    splits.away_game_runs += stat.runs;
+                    splits.away_home_runs += stat.homeRuns;
+                },
+                (false, false) => {
+                    splits.away_game_runs += stat.runs;
+                    splits.away_home_runs += stat.homeRuns;
+                }
+            }
+        }
+    }
+    Ok(park_splits)
+}
+
+// Run park factors fetched once per season and reused across the rows of a view
+#[derive(Default)]
+pub(crate) struct ParkFactors {
+    seasons: HashMap<String, HashMap<i32, ParkSplits>>
+}
+
+impl ParkFactors {
+    pub(crate) fn get(&mut self, season: &str, team_id: i32) -> Option<f64> {
+        if !self.seasons.contains_key(season) {
+            // Best effort, stats are shown unadjusted when the splits can't be fetched
+            let park_splits = season.parse::<i32>().ok().and_then(|season| get_park_splits(season).ok()).unwrap_or_default();
+            self.seasons.insert(season.to_string(), park_splits);
+        }
+        self.seasons.get(season)?.get(&team_id)?.run_factor()
+    }
+}
+
+fn format_factor(factor: Option<f64>) -> String {
+    factor.map_or("-".to_string(), |factor| format!("{:.0}", 100.0 * factor))
+}
+
+fn format_per_game(count: i32, games: i32) -> String {
+    ParkSplits::per_game(count, games).map_or("-".to_string(), |per_game| format!("{per_game:.2}"))
+}
+
+fn format_distance(distance: Option<i32>) -> String {
+    distance.map_or("-".to_string(), |distance| distance.to_string())
+}
+
+pub(crate) fn display_parks(query: &[String]) -> Result<(), QueryError> {
+    const COLS: usize = 15;
+
+    let (_, options) = split_options(query);
+    let season = get_season(&options)?;
+
+    let park_splits = get_park_splits(season)?;
+    let teams: Teams = get(teams_url!(season))?.json()?;
+    let venue_ids: Vec<String> = teams.teams.iter().map(|team| team.venue.id.to_string()).collect();
+    let venues: Venues = get(venues_url!(venue_ids.join(","), season))?.json()?;
+
+    let default_splits = ParkSplits::default();
+    let mut parks: Vec<(&TeamInfo, Option<&Venue>, &ParkSplits)> = teams.teams.iter().map(|team| (
+        team,
+        venues.venues.iter().find(|venue| venue.id == team.venue.id),
+        park_splits.get(&team.id).unwrap_or(&default_splits)
+    )).collect();
+    parks.sort_by(|(_, _, splits0), (_, _, splits1)| splits1.run_factor().unwrap_or(0.0).total_cmp(&splits0.run_factor().unwrap_or(0.0)));
+
+    let mut table = Table::new();
+    table.style = TableStyle::thin();
+    table.add_row(row!(TableCell::builder(format!("{season} Park Factors")).col_span(COLS).build()));
+    table.add_row(row!("Team", "Venue", "Capacity", "Roof", "LF", "LCF", "CF", "RCF", "RF",
+        "Runs PF", "HR PF", "Home R/G", "Away R/G", "Home HR/G", "Away HR/G"));
+    for (team, venue, splits) in parks {
+        let field_info = venue.and_then(|venue| venue.fieldInfo.as_ref());
+        let field = |distance: fn(&FieldInfo) -> Option<i32>| format_distance(field_info.and_then(distance));
+        table.add_row(row!(
+            &team.name,
+            venue.map_or("", |venue| venue.name.as_str()),
+            field(|field_info| field_info.capacity),
+            field_info.and_then(|field_info| field_info.roofType.as_deref()).unwrap_or("-"),
+            field(|field_info| field_info.leftLine),
+            field(|field_info| field_info.leftCenter),
+            field(|field_info| field_info.center),
+            field(|field_info| field_info.rightCenter),
+            field(|field_info| field_info.rightLine),
+            format_factor(splits.run_factor()),
+            format_factor(splits.home_run_factor()),
+            format_per_game(splits.home_game_runs, splits.home_games),
+            format_per_game(splits.away_game_runs, splits.away_games),
+            format_per_game(splits.home_home_runs, splits.home_games),
+            format_per_game(splits.away_home_runs, splits.away_games)
+        ));
+    }
+    println!("\n{}", table.render());
+    Ok(())
+}
@@ -3,7 +3,9 @@ use serde::Deserialize;
 use term_table::{row, Table};
 use term_table::row::Row;
 use term_table::table_cell::TableCell;
-use crate::stats::{Stat};
+use crate::stats::{no_stats_error, Stat};
+use crate::query::{QueryError, REGULAR_SEASON};
+use crate::sabermetrics::{sabermetric_inputs, sabermetric_pitching_header, sabermetric_pitching_row, PitchingLine};
 
 #[derive(Deserialize)]
 pub(crate) struct PitchingStats {
@@ -52,11 +54,37 @@ pub(crate) struct Pitcher {
     #[serde(default)]
     pub(crate) saves: i32,
     #[serde(default)]
-    pub(crate) saveOpportunities: i32
+    pub(crate) saveOpportunities: i32,
+    #[serde(default)]
+    pub(crate) homeRuns: i32,
+    #[serde(default)]
+    pub(crate) battersFaced: i32,
+    #[serde(default)]
+    pub(crate) airOuts: i32,
+    #[serde(default)]
+    pub(crate) gamesPlayed: i32,
+    #[serde(default)]
+    pub(crate) gamesFinished: i32,
+    #[serde(default)]
+    pub(crate) completeGames: i32,
+    #[serde(default)]
+    pub(crate) shutouts: i32,
+    #[serde(default)]
+    pub(crate) runs: i32,
+    #[serde(default)]
+    pub(crate) balks: i32,
+    #[serde(default)]
+    pub(crate) wildPitches: i32,
+    #[serde(default)]
+    pub(crate) intentionalWalks: i32,
+    #[serde(default)]
+    pub(crate) atBats: i32,
+    #[serde(default)]
+    pub(crate) sacFlies: i32
 }
 
 macro_rules! pitching_stats_url {
-    () => { "https://statsapi.mlb.com/api/v1/people/{}/stats?stats={}&group=pitching" };
+    () => { "https://statsapi.mlb.com/api/v1/people/{}/stats?stats={}&group=pitching&gameType={}" };
 }
 
 macro_rules! pitching_header {
@@ -80,13 +108,25 @@ macro_rules! pitching_row {
 pub(crate) use pitching_row;
 
 pub(crate) fn get_season_pitching_stats(player_id: i32) -> reqwest::Result<PitchingStats> {
-    let url = format!(pitching_stats_url!(), player_id, "season");
-    Ok(get(url)?.json()?)
+    let url = format!(pitching_stats_url!(), player_id, "season", REGULAR_SEASON);
+    get(url)?.json()
+}
+
+pub(crate) fn format_pitcher_summary(stat: Option<&Pitcher>) -> String {
+    match stat {
+        Some(stat) => format!("{}-{}, {} ERA, {} WHIP, {} K/9", stat.wins, stat.losses, stat.era, stat.whip, stat.strikeoutsPer9Inn),
+        None => "No season stats".to_string()
+    }
 }
 
-pub(crate) fn get_pitching_stats(player_id: i32, season_type: &str) -> reqwest::Result<PitchingStats> {
-    let url = format!(pitching_stats_url!(), player_id, season_type);
-    Ok(get(url)?.json()?)
+pub(crate) fn get_pitcher_summary(player_id: i32) -> reqwest::Result<String> {
+    let stats = get_season_pitching_stats(player_id)?;
+    Ok(format_pitcher_summary(stats.stats.first().and_then(|stat| stat.splits.first()).map(|split| &split.stat)))
+}
+
+pub(crate) fn get_pitching_stats(player_id: i32, season_type: &str, game_type: &str) -> reqwest::Result<PitchingStats> {
+    let url = format!(pitching_stats_url!(), player_id, season_type, game_type);
+    get(url)?.json()
 }
 
 pub(crate) fn get_pitching_row(stats: &PitchingStats) -> Row {
@@ -94,18 +134,35 @@ pub(crate) fn get_pitching_row(stats: &PitchingStats) -> Row {
     pitching_row!(&split.player.fullName, &split.stat)
 }
 
-pub(crate) fn display_pitching_stats(player_id: i32, season_type: &str) -> reqwest::Result<()> {
-    let stats: PitchingStats = get_pitching_stats(player_id, season_type)?;
+pub(crate) fn display_pitching_stats(player_id: i32, season_type: &str, game_type: &str, show_sabermetrics: bool)
+    -> Result<(), QueryError> {
+    let stats: PitchingStats = get_pitching_stats(player_id, season_type, game_type)?;
+    let Some(first_split) = stats.stats.first().and_then(|stat| stat.splits.first()) else {
+        return Err(no_stats_error(season_type, game_type));
+    };
+
+    let (league_constants, mut park_factors) = sabermetric_inputs(show_sabermetrics);
 
     let mut table = Table::new();
     table.add_row(pitching_header!("Year"));
 
+    let mut sabermetric_table = Table::new();
+    sabermetric_table.add_row(sabermetric_pitching_header!("Year"));
+
     for stat in &stats.stats {
         for split in &stat.splits {
             table.add_row(pitching_row!(&split.season, &split.stat));
+            if show_sabermetrics {
+                let park_factor = split.team.as_ref().and_then(|team| park_factors.get(&split.season, team.id));
+                sabermetric_table.add_row(sabermetric_pitching_row(&split.season, &PitchingLine::from(&split.stat),
+                    league_constants.get(&split.season), park_factor));
+            }
         }
     }
 
-    println!("\nPlayer: {}\n\nPitching Statistics:\n{}", &stats.stats[0].splits[0].player.fullName, table.render());
+    println!("\nPlayer: {}\n\nPitching Statistics:\n{}", &first_split.player.fullName, table.render());
+    if show_sabermetrics {
+        println!("Sabermetrics:\n{}", sabermetric_table.render());
+    }
     Ok(())
 }
@@ -1,3 +1,9 @@
+use std::collections::HashMap;
+use chrono::{Datelike, Utc};
+use std::env;
+use phf_macros::phf_map;
+use terminal_size::{terminal_size, Width};
+
 #[derive(thiserror::Error, Debug)]
 pub enum QueryError {
     #[error("{0}")]
@@ -8,6 +14,10 @@ pub enum QueryError {
     EntryError(String),
     #[error("No Database found for query {0}")]
     DataBaseError(String),
+    #[error("Invalid value {1} for option {0}")]
+    OptionError(String, String),
+    #[error("Invalid formula near {0}")]
+    FormulaError(String),
     #[error(transparent)]
     ReqwestError(#[from] reqwest::Error),
     #[error(transparent)]
@@ -25,3 +35,74 @@ macro_rules! get_query_param {
     };
 }
 pub(crate) use get_query_param;
+
+
+const GAME_TYPES: phf::Map<&'static str, &'static str> = phf_map! {
+    "regular" => "R",
+    "postseason" => "P",
+    "spring" => "S",
+    "allstar" => "A",
+    "wildcard" => "F",
+    "division" => "D",
+    "lcs" => "L",
+    "ws" => "W",
+};
+
+// Options that are switched on by name alone and never take a value
+const FLAGS: [&str; 11] = ["home", "away", "one-run", "extras", "qualified", "all", "career", "single-season", "ascending", "eras",
+    "sabermetrics"];
+
+pub(crate) const REGULAR_SEASON: &str = "R";
+pub(crate) const POSTSEASON: &str = "P";
+
+// Schedule requests leave the game type out unless one is given, so a day's schedule has every kind of game
+pub(crate) fn get_schedule_game_type(options: &HashMap<String, String>) -> Result<Option<&'static str>, QueryError> {
+    const GAME_TYPE_OPTION: &str = "game-type";
+
+    options.get(GAME_TYPE_OPTION).map(|game_type| GAME_TYPES.get(game_type.to_ascii_lowercase().as_str()).copied()
+        .ok_or(QueryError::OptionError(GAME_TYPE_OPTION.to_string(), game_type.clone()))).transpose()
+}
+
+// Stats and leaders default to the regular season like their endpoints do
+pub(crate) fn get_game_type(options: &HashMap<String, String>) -> Result<&'static str, QueryError> {
+    Ok(get_schedule_game_type(options)?.unwrap_or(REGULAR_SEASON))
+}
+
+pub(crate) fn get_season(options: &HashMap<String, String>) -> Result<i32, QueryError> {
+    const SEASON_OPTION: &str = "season";
+
+    match options.get(SEASON_OPTION) {
+        Some(season) => season.parse::<i32>()
+            .map_err(|_| QueryError::OptionError(SEASON_OPTION.to_string(), season.clone())),
+        None => Ok(Utc::now().year())
+    }
+}
+
+pub(crate) fn split_options(query: &[String]) -> (Vec<String>, HashMap<String, String>) {
+    const OPTION_PREFIX: &str = "--";
+
+    let mut args: Vec<String> = Vec::with_capacity(query.len());
+    let mut options: HashMap<String, String> = HashMap::new();
+    let mut tokens = query.iter();
+    while let Some(token) = tokens.next() {
+        match token.strip_prefix(OPTION_PREFIX) {
+            Some(name) => {
+                let name = name.to_ascii_lowercase();
+                let value = if FLAGS.contains(&name.as_str()) { "".to_string() }
+                    else { tokens.next().cloned().unwrap_or_default() };
+                options.insert(name, value);
+            },
+            None => args.push(token.clone())
+        }
+    }
+    (args, options)
+}
+
+// $COLUMNS is rarely exported to child processes, so it only overrides the size reported by the terminal
+pub(crate) fn terminal_width() -> usize {
+    const DEFAULT_WIDTH: usize = 120;
+
+    env::var("COLUMNS").ok().and_then(|columns| columns.parse::<usize>().ok())
+        .or_else(|| terminal_size().map(|(Width(width), _)| width as usize))
+        .unwrap_or(DEFAULT_WIDTH)
+}
@@ -0,0 +1,445 @@
+use std::collections::HashMap;
+use term_table::row;
+use term_table::row::Row;
+use term_table::table_cell::TableCell;
+use crate::formula::parse_innings;
+use crate::hitting_stats::Batter;
+use crate::league_averages::{get_league_averages, AverageStat, LeagueSeasonAverages};
+use crate::parks::ParkFactors;
+use crate::pitching_stats::Pitcher;
+use crate::query::QueryError;
+
+// Published wOBA weights for unintentional walks, HBP, singles, doubles, triples and home runs, rescaled
+// each season so that league wOBA matches league OBP
+const WOBA_WEIGHTS: [f64; 6] = [0.69, 0.72, 0.88, 1.25, 1.58, 2.03];
+// The run scale the weights above were published with
+const BASE_WOBA_SCALE: f64 = 1.2;
+// Home runs per fly ball are not in the league averages so xFIP uses the long run league rate
+const LEAGUE_HR_PER_FLY_BALL: f64 = 0.11;
+
+pub(crate) struct LeagueConstants {
+    obp: f64,
+    slg: f64,
+    woba_weights: [f64; 6],
+    woba_scale: f64,
+    runs_per_plate_appearance: f64,
+    era: f64,
+    fip_constant: f64
+}
+
+pub(crate) struct HittingLine {
+    plate_appearances: f64,
+    at_bats: f64,
+    hits: f64,
+    doubles: f64,
+    triples: f64,
+    home_runs: f64,
+    walks: f64,
+    intentional_walks: f64,
+    hit_by_pitch: f64,
+    sac_flies: f64,
+    strikeouts: f64
+}
+
+pub(crate) struct PitchingLine {
+    innings: f64,
+    home_runs: f64,
+    walks: f64,
+    hit_by_pitch: f64,
+    strikeouts: f64,
+    batters_faced: f64,
+    fly_balls: f64,
+    earned_runs: f64
+}
+
+// A player plays half their games at home, so only half of the park effect applies to their stats
+fn get_park_adjustment(park_factor: Option<f64>) -> f64 {
+    park_factor.map_or(1.0, |park_factor| (1.0 + park_factor) / 2.0)
+}
+
+pub(crate) fn ratio(numerator: f64, denominator: f64) -> Option<f64> {
+    if denominator == 0.0 { None } else { Some(numerator / denominator) }
+}
+
+// Expected winning percentage from runs scored and allowed
+pub(crate) fn get_pythagorean_pct(runs_scored: i32, runs_allowed: i32) -> f64 {
+    const PYTHAGOREAN_EXPONENT: f64 = 1.83;
+
+    let scored = (runs_scored as f64).powf(PYTHAGOREAN_EXPONENT);
+    let allowed = (runs_allowed as f64).powf(PYTHAGOREAN_EXPONENT);
+    ratio(scored, scored + allowed).unwrap_or(0.0)
+}
+
+fn get_value(values: &HashMap<String, f64>, name: &str) -> f64 {
+    values.get(&name.to_ascii_lowercase()).copied().unwrap_or(0.0)
+}
+
+impl From<&Batter> for HittingLine {
+    fn from(batter: &Batter) -> HittingLine {
+        HittingLine {
+            plate_appearances: batter.plateAppearances as f64,
+            at_bats: batter.atBats as f64,
+            hits: batter.hits as f64,
+            doubles: batter.doubles as f64,
+            triples: batter.triples as f64,
+            home_runs: batter.homeRuns as f64,
+            walks: batter.baseOnBalls as f64,
+            intentional_walks: batter.intentionalWalks as f64,
+            hit_by_pitch: batter.hitByPitch as f64,
+            sac_flies: batter.sacFlies as f64,
+            strikeouts: batter.strikeOuts as f64
+        }
+    }
+}
+
+impl From<&Pitcher> for PitchingLine {
+    fn from(pitcher: &Pitcher) -> PitchingLine {
+        PitchingLine {
+            innings: parse_innings(&pitcher.inningsPitched).unwrap_or(0.0),
+            home_runs: pitcher.homeRuns as f64,
+            walks: pitcher.baseOnBalls as f64,
+            hit_by_pitch: pitcher.hitByPitch as f64,
+            strikeouts: pitcher.strikeOuts as f64,
+            batters_faced: pitcher.battersFaced as f64,
+            fly_balls: (pitcher.airOuts + pitcher.homeRuns) as f64,
+            earned_runs: pitcher.earnedRuns as f64
+        }
+    }
+}
+
+impl HittingLine {
+    // Fields of the bulk stats endpoint, keyed by lower case name
+    pub(crate) fn from_values(values: &HashMap<String, f64>) -> HittingLine {
+        HittingLine {
+            plate_appearances: get_value(values, "plateAppearances"),
+            at_bats: get_value(values, "atBats"),
+            hits: get_value(values, "hits"),
+            doubles: get_value(values, "doubles"),
+            triples: get_value(values, "triples"),
+            home_runs: get_value(values, "homeRuns"),
+            walks: get_value(values, "baseOnBalls"),
+            intentional_walks: get_value(values, "intentionalWalks"),
+            hit_by_pitch: get_value(values, "hitByPitch"),
+            sac_flies: get_value(values, "sacFlies"),
+            strikeouts: get_value(values, "strikeOuts")
+        }
+    }
+
+    fn singles(&self) -> f64 {
+        self.hits - self.doubles - self.triples - self.home_runs
+    }
+
+    pub(crate) fn obp(&self) -> Option<f64> {
+        ratio(self.hits + self.walks + self.hit_by_pitch, self.at_bats + self.walks + self.hit_by_pitch + self.sac_flies)
+    }
+
+    pub(crate) fn slg(&self) -> Option<f64> {
+        ratio(self.singles() + 2.0 * self.doubles + 3.0 * self.triples + 4.0 * self.home_runs, self.at_bats)
+    }
+
+    fn unscaled_woba(&self, weights: &[f64; 6]) -> Option<f64> {
+        let events = [self.walks - self.intentional_walks, self.hit_by_pitch, self.singles(), self.doubles, self.triples, self.home_runs];
+        let total: f64 = weights.iter().zip(events.iter()).map(|(weight, count)| weight * count).sum();
+        ratio(total, self.at_bats + self.walks - self.intentional_walks + self.sac_flies + self.hit_by_pitch)
+    }
+
+    pub(crate) fn iso(&self) -> Option<f64> {
+        ratio(self.doubles + 2.0 * self.triples + 3.0 * self.home_runs, self.at_bats)
+    }
+
+    pub(crate) fn babip(&self) -> Option<f64> {
+        ratio(self.hits - self.home_runs, self.at_bats - self.strikeouts - self.home_runs + self.sac_flies)
+    }
+
+    pub(crate) fn woba(&self, league: &LeagueConstants) -> Option<f64> {
+        self.unscaled_woba(&league.woba_weights)
+    }
+
+    // League wOBA equals league OBP by construction of the weights
+    pub(crate) fn wrc_plus(&self, league: &LeagueConstants, park_factor: Option<f64>) -> Option<f64> {
+        let runs_above_average = (self.woba(league)? - league.obp) / league.woba_scale;
+        let park_runs = league.runs_per_plate_appearance * (1.0 - get_park_adjustment(park_factor));
+        ratio(100.0 * (runs_above_average + league.runs_per_plate_appearance + park_runs), league.runs_per_plate_appearance)
+    }
+
+    pub(crate) fn ops_plus(&self, league: &LeagueConstants, park_factor: Option<f64>) -> Option<f64> {
+        let ops_plus = 100.0 * (ratio(self.obp()?, league.obp)? + ratio(self.slg()?, league.slg)? - 1.0);
+        Some(ops_plus / get_park_adjustment(park_factor))
+    }
+
+    pub(crate) fn park_adjusted_ops(&self, park_factor: f64) -> Option<f64> {
+        Some((self.obp()? + self.slg()?) / get_park_adjustment(Some(park_factor)))
+    }
+}
+
+impl PitchingLine {
+    pub(crate) fn from_values(values: &HashMap<String, f64>) -> PitchingLine {
+        PitchingLine {
+            innings: get_value(values, "inningsPitched"),
+            home_runs: get_value(values, "homeRuns"),
+            walks: get_value(values, "baseOnBalls"),
+            hit_by_pitch: get_value(values, "hitByPitch"),
+            strikeouts: get_value(values, "strikeOuts"),
+            batters_faced: get_value(values, "battersFaced"),
+            fly_balls: get_value(values, "airOuts") + get_value(values, "homeRuns"),
+            earned_runs: get_value(values, "earnedRuns")
+        }
+    }
+
+    fn era(&self) -> Option<f64> {
+        ratio(9.0 * self.earned_runs, self.innings)
+    }
+
+    fn fip_with_home_runs(&self, home_runs: f64, league: &LeagueConstants) -> Option<f64> {
+        Some(ratio(13.0 * home_runs + 3.0 * (self.walks + self.hit_by_pitch) - 2.0 * self.strikeouts, self.innings)? + league.fip_constant)
+    }
+
+    pub(crate) fn fip(&self, league: &LeagueConstants) -> Option<f64> {
+        self.fip_with_home_runs(self.home_runs, league)
+    }
+
+    pub(crate) fn xfip(&self, league: &LeagueConstants) -> Option<f64> {
+        self.fip_with_home_runs(self.fly_balls * LEAGUE_HR_PER_FLY_BALL, league)
+    }
+
+    pub(crate) fn strikeout_minus_walk_pct(&self) -> Option<f64> {
+        ratio(100.0 * (self.strikeouts - self.walks), self.batters_faced)
+    }
+
+    pub(crate) fn era_minus(&self, league: &LeagueConstants, park_factor: Option<f64>) -> Option<f64> {
+        ratio(100.0 * self.era()? / get_park_adjustment(park_factor), league.era)
+    }
+
+    pub(crate) fn park_adjusted_era(&self, park_factor: f64) -> Option<f64> {
+        Some(self.era()? / get_park_adjustment(Some(park_factor)))
+    }
+}
+
+fn get_league_hitting_line(batting: &LeagueSeasonAverages) -> Option<HittingLine> {
+    Some(HittingLine {
+        plate_appearances: batting.get(AverageStat::PlateAppearances)?,
+        at_bats: batting.get(AverageStat::AtBats)?,
+        hits: batting.get(AverageStat::Hits)?,
+        doubles: batting.get(AverageStat::Doubles)?,
+        triples: batting.get(AverageStat::Triples)?,
+        home_runs: batting.get(AverageStat::HomeRuns)?,
+        walks: batting.get(AverageStat::Walks)?,
+        intentional_walks: batting.get(AverageStat::IntentionalWalks).unwrap_or(0.0),
+        hit_by_pitch: batting.get(AverageStat::HitByPitch).unwrap_or(0.0),
+        sac_flies: batting.get(AverageStat::SacFlies).unwrap_or(0.0),
+        strikeouts: batting.get(AverageStat::Strikeouts).unwrap_or(0.0)
+    })
+}
+
+fn get_constants(batting: &LeagueSeasonAverages, pitching: &LeagueSeasonAverages) -> Option<LeagueConstants> {
+    let league_line = get_league_hitting_line(batting)?;
+    let obp = league_line.obp()?;
+    let rescale = ratio(obp, league_line.unscaled_woba(&WOBA_WEIGHTS)?)?;
+
+    let era = pitching.get(AverageStat::Era)?;
+    let fip_components = 13.0 * pitching.get(AverageStat::HomeRuns)?
+        + 3.0 * (pitching.get(AverageStat::Walks)? + pitching.get(AverageStat::HitByPitch).unwrap_or(0.0))
+        - 2.0 * pitching.get(AverageStat::Strikeouts)?;
+    Some(LeagueConstants {
+        obp,
+        slg: league_line.slg()?,
+        woba_weights: WOBA_WEIGHTS.map(|weight| weight * rescale),
+        woba_scale: BASE_WOBA_SCALE * rescale,
+        runs_per_plate_appearance: ratio(batting.get(AverageStat::Runs)?, league_line.plate_appearances)?,
+        era,
+        fip_constant: era - ratio(fip_components, pitching.get(AverageStat::InningsPitched)?)?
+    })
+}
+
+// League constants keyed by season, from the per team per game league averages
+pub(crate) fn get_league_constants() -> Result<HashMap<String, LeagueConstants>, QueryError> {
+    let batting = get_league_averages(true)?;
+    let pitching = get_league_averages(false)?;
+    Ok(batting.iter().filter_map(|batting_averages| {
+        let pitching_averages = pitching.iter().find(|pitching_averages| pitching_averages.year == batting_averages.year)?;
+        Some((batting_averages.year.clone(), get_constants(batting_averages, pitching_averages)?))
+    }).collect())
+}
+
+// League constants and park factors take extra requests, so they're only fetched when a view shows league relative stats.
+// They're best effort so the rest of the view still shows when they can't be fetched
+pub(crate) fn sabermetric_inputs(show: bool) -> (HashMap<String, LeagueConstants>, ParkFactors) {
+    let league_constants = if show { get_league_constants().unwrap_or_default() } else { HashMap::new() };
+    (league_constants, ParkFactors::default())
+}
+
+pub(crate) fn format_stat(value: Option<f64>, decimals: usize) -> String {
+    value.map_or("-".to_string(), |value| format!("{value:.decimals$}"))
+}
+
+// Rate stats below one drop the leading zero, like the API's own ".312"
+pub(crate) fn format_rate(value: Option<f64>) -> String {
+    value.map_or("-".to_string(), |value| {
+        let rate = format!("{value:.3}");
+        if value.abs() < 1.0 { rate.replacen("0.", ".", 1) } else { rate }
+    })
+}
+
+// Stats that need the league constants or park factors, which take extra requests to fetch
+pub(crate) const LEAGUE_RELATIVE_STATS: [&str; 8] = ["woba", "wrcplus", "opsplus", "parkops", "fip", "xfip", "eraminus", "parkera"];
+
+// League relative stats are park adjusted when the player's home park factor is known
+pub(crate) fn get_hitting_values(line: &HittingLine, league: Option<&LeagueConstants>, park_factor: Option<f64>)
+    -> [(&'static str, Option<f64>); 6] {
+    [
+        ("iso", line.iso()),
+        ("babip", line.babip()),
+        ("woba", league.and_then(|league| line.woba(league))),
+        ("wrcplus", league.and_then(|league| line.wrc_plus(league, park_factor))),
+        ("opsplus", league.and_then(|league| line.ops_plus(league, park_factor))),
+        ("parkops", park_factor.and_then(|park_factor| line.park_adjusted_ops(park_factor)))
+    ]
+}
+
+pub(crate) fn get_pitching_values(line: &PitchingLine, league: Option<&LeagueConstants>, park_factor: Option<f64>)
+    -> [(&'static str, Option<f64>); 5] {
+    [
+        ("fip", league.and_then(|league| line.fip(league))),
+        ("xfip", league.and_then(|league| line.xfip(league))),
+        ("kbbpct", line.strikeout_minus_walk_pct()),
+        ("eraminus", league.and_then(|league| line.era_minus(league, park_factor))),
+        ("parkera", park_factor.and_then(|park_factor| line.park_adjusted_era(park_factor)))
+    ]
+}
+
+macro_rules! sabermetric_hitting_header {
+    ($col0:expr) => {
+        row!($col0, "ISO", "BABIP", "wOBA", "wRC+", "OPS+", "PF", "Park OPS")
+    };
+}
+pub(crate) use sabermetric_hitting_header;
+
+macro_rules! sabermetric_pitching_header {
+    ($col0:expr) => {
+        row!($col0, "FIP", "xFIP", "K-BB%", "ERA-", "PF", "Park ERA")
+    };
+}
+pub(crate) use sabermetric_pitching_header;
+
+pub(crate) fn sabermetric_hitting_row(col0: &str, line: &HittingLine, league: Option<&LeagueConstants>, park_factor: Option<f64>) -> Row {
+    let [iso, babip, woba, wrc_plus, ops_plus, park_ops] = get_hitting_values(line, league, park_factor).map(|(_, value)| value);
+    row!(col0, format_rate(iso), format_rate(babip), format_rate(woba), format_stat(wrc_plus, 0), format_stat(ops_plus, 0),
+        format_stat(park_factor.map(|park_factor| 100.0 * park_factor), 0), format_rate(park_ops))
+}
+
+pub(crate) fn sabermetric_pitching_row(col0: &str, line: &PitchingLine, league: Option<&LeagueConstants>, park_factor: Option<f64>) -> Row {
+    let [fip, xfip, strikeout_minus_walk_pct, era_minus, park_era] = get_pitching_values(line, league, park_factor).map(|(_, value)| value);
+    row!(col0, format_stat(fip, 2), format_stat(xfip, 2), format_stat(strikeout_minus_walk_pct, 1), format_stat(era_minus, 0),
+        format_stat(park_factor.map(|park_factor| 100.0 * park_factor), 0), format_stat(park_era, 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::league_averages::parse_averages_page;
+
+    fn season_averages(is_batting: bool) -> LeagueSeasonAverages {
+        let html = if is_batting { include_str!("../tests/fixtures/bat.shtml") } else { include_str!("../tests/fixtures/pitch.shtml") };
+        parse_averages_page(html, is_batting).unwrap().remove(0)
+    }
+
+    fn league() -> LeagueConstants {
+        get_constants(&season_averages(true), &season_averages(false)).unwrap()
+    }
+
+    // The league's own pitching line, with earned runs backed out of its ERA
+    fn league_pitching_line() -> PitchingLine {
+        let pitching = season_averages(false);
+        let innings = pitching.get(AverageStat::InningsPitched).unwrap();
+        PitchingLine {
+            innings,
+            home_runs: pitching.get(AverageStat::HomeRuns).unwrap(),
+            walks: pitching.get(AverageStat::Walks).unwrap(),
+            hit_by_pitch: pitching.get(AverageStat::HitByPitch).unwrap(),
+            strikeouts: pitching.get(AverageStat::Strikeouts).unwrap(),
+            batters_faced: pitching.get(AverageStat::BattersFaced).unwrap(),
+            fly_balls: 0.0,
+            earned_runs: pitching.get(AverageStat::Era).unwrap() * innings / 9.0
+        }
+    }
+
+    fn hitting_line() -> HittingLine {
+        HittingLine {
+            plate_appearances: 600.0,
+            at_bats: 520.0,
+            hits: 150.0,
+            doubles: 30.0,
+            triples: 5.0,
+            home_runs: 25.0,
+            walks: 60.0,
+            intentional_walks: 5.0,
+            hit_by_pitch: 10.0,
+            sac_flies: 10.0,
+            strikeouts: 120.0
+        }
+    }
+
+    fn assert_close(value: Option<f64>, expected: f64) {
+        assert!(value.is_some_and(|value| (value - expected).abs() < 1e-9), "{value:?} != {expected}");
+    }
+
+    #[test]
+    fn league_average_hitter_scores_one_hundred() {
+        let league = league();
+        let line = get_league_hitting_line(&season_averages(true)).unwrap();
+        assert_close(line.wrc_plus(&league, None), 100.0);
+        assert_close(line.ops_plus(&league, None), 100.0);
+    }
+
+    #[test]
+    fn hitter_friendly_park_lowers_league_relative_stats() {
+        let league = league();
+        let line = get_league_hitting_line(&season_averages(true)).unwrap();
+        assert!(line.wrc_plus(&league, Some(1.1)).unwrap() < 100.0);
+        assert!(line.ops_plus(&league, Some(1.1)).unwrap() < 100.0);
+        assert_close(line.wrc_plus(&league, Some(1.0)), 100.0);
+    }
+
+    #[test]
+    fn league_average_pitcher_matches_league_era() {
+        let league = league();
+        let line = league_pitching_line();
+        assert_close(line.era_minus(&league, None), 100.0);
+        assert_close(line.fip(&league), league.era);
+        assert_close(Some(league.era), 4.08);
+    }
+
+    #[test]
+    fn woba_weights_are_rescaled_to_league_obp() {
+        let league = league();
+        let line = get_league_hitting_line(&season_averages(true)).unwrap();
+        assert_close(line.woba(&league), league.obp);
+        let rescale = league.woba_scale / BASE_WOBA_SCALE;
+        for (weight, base_weight) in league.woba_weights.iter().zip(WOBA_WEIGHTS.iter()) {
+            assert_close(Some(*weight), base_weight * rescale);
+        }
+    }
+
+    #[test]
+    fn iso_and_babip_use_their_own_denominators() {
+        let line = hitting_line();
+        assert_close(line.iso(), (30.0 + 2.0 * 5.0 + 3.0 * 25.0) / 520.0);
+        assert_close(line.babip(), (150.0 - 25.0) / (520.0 - 120.0 - 25.0 + 10.0));
+    }
+
+    #[test]
+    fn xfip_replaces_home_runs_with_league_rate_on_fly_balls() {
+        let league = league();
+        let line = PitchingLine { home_runs: 20.0, fly_balls: 200.0, ..league_pitching_line() };
+        let expected = PitchingLine { home_runs: 200.0 * LEAGUE_HR_PER_FLY_BALL, ..league_pitching_line() }.fip(&league);
+        assert_close(line.xfip(&league), expected.unwrap());
+    }
+
+    #[test]
+    fn empty_lines_have_no_rates() {
+        let line = HittingLine { at_bats: 0.0, ..hitting_line() };
+        assert_eq!(line.iso(), None);
+        assert_eq!(format_rate(line.iso()), "-");
+        assert_eq!(format_rate(Some(0.3127)), ".313");
+    }
+}
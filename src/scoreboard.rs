@@ -0,0 +1,123 @@
+use reqwest::blocking::get;
+use term_table::{row, Table, TableStyle};
+use term_table::row::Row;
+use term_table::table_cell::{Alignment, TableCell};
+use crate::game::{get_eastern_standard_time, schedule_game_types, Game, LineScore, PlayingTeam, Schedule};
+use crate::stats::Player;
+use crate::query::{get_schedule_game_type, split_options, terminal_width, QueryError};
+
+macro_rules! scoreboard_url {
+    ($game_type:expr) => {
+        format!("https://statsapi.mlb.com/api/v1/schedule?sportId=1{}&hydrate=team,linescore,probablePitcher,decisions",
+            schedule_game_types($game_type))
+    };
+}
+
+fn last_name(person: Option<&Player>) -> String {
+    match person {
+        Some(person) => person.fullName.rsplit(' ').next().unwrap_or_default().to_string(),
+        None => "TBD".to_string()
+    }
+}
+
+fn probable_pitcher(team: &PlayingTeam) -> Option<&Player> {
+    team.probablePitcher.as_ref().map(|pitcher| &pitcher.person)
+}
+
+fn get_card_status(game: &Game) -> String {
+    match (game.status.abstractGameState.as_str(), &game.linescore) {
+        ("Live", Some(line_score)) if game.status.detailedState == "In Progress" => format!(
+            "{} {}, {} Out", line_score.inningState, line_score.currentInningOrdinal, line_score.outs),
+        ("Preview", _) => get_eastern_standard_time(&game.gameDate),
+        _ => game.status.detailedState.clone()
+    }
+}
+
+fn get_card_footer(game: &Game) -> String {
+    match game.status.abstractGameState.as_str() {
+        "Live" => {
+            let line_score = game.linescore.as_ref();
+            let pitcher = line_score.and_then(|line_score| line_score.defense.as_ref())
+                .map_or("TBD".to_string(), |defense| last_name(defense.pitcher.as_ref()));
+            let batter = line_score.and_then(|line_score| line_score.offense.as_ref())
+                .map_or("TBD".to_string(), |offense| last_name(offense.batter.as_ref()));
+            format!("P: {pitcher}  AB: {batter}")
+        },
+        "Final" => match &game.decisions {
+            Some(decisions) => {
+                let mut footer = format!("W: {}  L: {}", last_name(decisions.winner.as_ref()), last_name(decisions.loser.as_ref()));
+                if decisions.save.is_some() {
+                    footer.push_str(&format!("  S: {}", last_name(decisions.save.as_ref())));
+                }
+                footer
+            },
+            None => "".to_string()
+        },
+        _ => format!("{} vs {}", last_name(probable_pitcher(&game.teams.away)), last_name(probable_pitcher(&game.teams.home)))
+    }
+}
+
+fn get_card(game: &Game) -> Vec<String> {
+    let empty_line_score = LineScore::default();
+    let line_score = game.linescore.as_ref().unwrap_or(&empty_line_score);
+
+    let mut header: Vec<String> = vec!["".to_string()];
+    let mut away: Vec<String> = vec![game.teams.away.team.abbreviation.clone()];
+    let mut home: Vec<String> = vec![game.teams.home.team.abbreviation.clone()];
+    for inning in &line_score.innings {
+        header.push(inning.num.to_string());
+        away.push(inning.away.runs.map_or(" ".to_string(), |runs| runs.to_string()));
+        home.push(inning.home.runs.map_or(" ".to_string(), |runs| runs.to_string()));
+    }
+    for num in (line_score.innings.len() as i32 + 1)..=line_score.scheduledInnings {
+        header.push(num.to_string());
+        away.push(" ".to_string());
+        home.push(" ".to_string());
+    }
+
+    let teams = &line_score.teams;
+    header.append(&mut vec!["R".to_string(), "H".to_string(), "E".to_string()]);
+    away.append(&mut vec![teams.away.runs.unwrap_or(0).to_string(), teams.away.hits.to_string(), teams.away.errors.to_string()]);
+    home.append(&mut vec![teams.home.runs.unwrap_or(0).to_string(), teams.home.hits.to_string(), teams.home.errors.to_string()]);
+    let cols = header.len();
+
+    let mut card = Table::new();
+    card.style = TableStyle::thin();
+    card.add_row(row!(TableCell::builder(get_card_status(game)).col_span(cols).alignment(Alignment::Center).build()));
+    card.add_row(Row::new(header));
+    card.add_row(Row::new(away));
+    card.add_row(Row::new(home));
+    card.add_row(row!(TableCell::builder(get_card_footer(game)).col_span(cols).build()));
+    card.render().lines().map(|line| line.to_string()).collect()
+}
+
+fn display_scoreboard(cards: Vec<Vec<String>>) {
+    const GAP: usize = 2;
+
+    let card_width = cards.iter().flatten().map(|line| line.chars().count()).max().unwrap_or(0);
+    let cards_per_row = (terminal_width() / (card_width + GAP)).max(1);
+
+    for card_row in cards.chunks(cards_per_row) {
+        let height = card_row.iter().map(|card| card.len()).max().unwrap_or(0);
+        for i in 0..height {
+            let line: Vec<String> = card_row.iter().map(|card| {
+                let card_line = card.get(i).map_or("", |line| line.as_str());
+                format!("{}{}", card_line, " ".repeat(card_width - card_line.chars().count()))
+            }).collect();
+            println!("{}", line.join(&" ".repeat(GAP)).trim_end());
+        }
+        println!();
+    }
+}
+
+pub(crate) fn scoreboard_query(query: &[String]) -> Result<(), QueryError> {
+    let (_, options) = split_options(query);
+    let game_type = get_schedule_game_type(&options)?;
+
+    let schedule: Schedule = get(scoreboard_url!(game_type))?.json()?;
+    let Some(date) = schedule.dates.first() else {
+        return Err(QueryError::GameError("No games today".to_string()));
+    };
+    display_scoreboard(date.games.iter().map(get_card).collect());
+    Ok(())
+}
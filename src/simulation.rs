@@ -0,0 +1,332 @@
+use std::cmp::Ordering;
+
+pub(crate) struct SimTeam {
+    pub(crate) id: i32,
+    pub(crate) league: i32,
+    pub(crate) division: i32,
+    pub(crate) strength: f64
+}
+
+pub(crate) struct SimGame {
+    pub(crate) away: i32,
+    pub(crate) home: i32,
+    pub(crate) winner: Option<i32>
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct Odds {
+    pub(crate) division: f64,
+    pub(crate) wild_card: f64,
+    pub(crate) bye: f64
+}
+
+// Postseason spots per league, byes are the top seeds that skip the first round
+pub(crate) struct PlayoffFormat {
+    pub(crate) division_winners: usize,
+    pub(crate) wild_cards: usize,
+    pub(crate) byes: usize,
+    pub(crate) first_round: &'static str,
+    // Seeds paired in the first round, better seed first
+    pub(crate) matchups: &'static [(usize, usize)]
+}
+
+// Seasons with three divisions per league, 2020's expanded one-off postseason is left out
+pub(crate) fn get_playoff_format(season: i32) -> Option<PlayoffFormat> {
+    match season {
+        2022.. => Some(PlayoffFormat {
+            division_winners: 3, wild_cards: 3, byes: 2, first_round: "Wild Card Series", matchups: &[(3, 6), (4, 5)]
+        }),
+        2012..=2019 | 2021 => Some(PlayoffFormat {
+            division_winners: 3, wild_cards: 2, byes: 3, first_round: "Wild Card Game", matchups: &[(4, 5)]
+        }),
+        1995..=2011 => Some(PlayoffFormat {
+            division_winners: 3, wild_cards: 1, byes: 0, first_round: "Division Series", matchups: &[]
+        }),
+        _ => None
+    }
+}
+
+const HOME_FIELD_ADVANTAGE: f64 = 0.02;
+
+// SplitMix64, good enough for simulations and keeps runs reproducible from a seed
+struct Random(u64);
+
+impl Random {
+    fn next_f64(&mut self) -> f64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        ((z ^ (z >> 31)) >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[derive(Clone)]
+struct Season {
+    wins: Vec<i32>,
+    losses: Vec<i32>,
+    head_to_head: Vec<Vec<i32>>,
+    division_record: Vec<(i32, i32)>,
+    league_record: Vec<(i32, i32)>
+}
+
+fn pct(wins: i32, losses: i32) -> f64 {
+    if wins + losses == 0 { 0.0 } else { wins as f64 / (wins + losses) as f64 }
+}
+
+impl Season {
+    fn new(team_count: usize) -> Season {
+        Season {
+            wins: vec![0; team_count],
+            losses: vec![0; team_count],
+            head_to_head: vec![vec![0; team_count]; team_count],
+            division_record: vec![(0, 0); team_count],
+            league_record: vec![(0, 0); team_count]
+        }
+    }
+
+    fn add_result(&mut self, teams: &[SimTeam], winner: usize, loser: usize) {
+        self.wins[winner] += 1;
+        self.losses[loser] += 1;
+        self.head_to_head[winner][loser] += 1;
+        if teams[winner].league == teams[loser].league {
+            self.league_record[winner].0 += 1;
+            self.league_record[loser].1 += 1;
+            if teams[winner].division == teams[loser].division {
+                self.division_record[winner].0 += 1;
+                self.division_record[loser].1 += 1;
+            }
+        }
+    }
+
+    fn pct(&self, team: usize) -> f64 {
+        pct(self.wins[team], self.losses[team])
+    }
+
+    fn head_to_head_pct(&self, team: usize, tied: &[usize]) -> f64 {
+        let wins: i32 = tied.iter().map(|&opp| self.head_to_head[team][opp]).sum();
+        let losses: i32 = tied.iter().map(|&opp| self.head_to_head[opp][team]).sum();
+        pct(wins, losses)
+    }
+
+    // Ties are broken by head-to-head record among the tied teams, then intradivision and intraleague record
+    fn rank(&self, mut group: Vec<usize>, random: &mut Random) -> Vec<usize> {
+        group.sort_by(|&team0, &team1| self.pct(team1).total_cmp(&self.pct(team0)));
+
+        let mut ranked: Vec<usize> = Vec::with_capacity(group.len());
+        let mut start = 0;
+        while start < group.len() {
+            let mut end = start + 1;
+            while end < group.len() && self.pct(group[end]) == self.pct(group[start]) {
+                end += 1;
+            }
+            let tied = &group[start..end];
+            let mut keys: Vec<(usize, [f64; 4])> = tied.iter().map(|&team| (team, [
+                self.head_to_head_pct(team, tied),
+                pct(self.division_record[team].0, self.division_record[team].1),
+                pct(self.league_record[team].0, self.league_record[team].1),
+                random.next_f64()
+            ])).collect();
+            keys.sort_by(|(_, key0), (_, key1)| key1.iter().zip(key0.iter())
+                .map(|(value1, value0)| value1.total_cmp(value0))
+                .find(|ordering| *ordering != Ordering::Equal).unwrap_or(Ordering::Equal));
+            ranked.extend(keys.into_iter().map(|(team, _)| team));
+            start = end;
+        }
+        ranked
+    }
+}
+
+fn win_probability(home: &SimTeam, away: &SimTeam) -> f64 {
+    let (home_strength, away_strength) = (home.strength, away.strength);
+    let denominator = home_strength + away_strength - 2.0 * home_strength * away_strength;
+    let log5 = if denominator == 0.0 { 0.5 } else { (home_strength - home_strength * away_strength) / denominator };
+    (log5 + HOME_FIELD_ADVANTAGE).clamp(0.0, 1.0)
+}
+
+fn get_index(teams: &[SimTeam], id: i32) -> Option<usize> {
+    teams.iter().position(|team| team.id == id)
+}
+
+pub(crate) fn simulate(teams: &[SimTeam], games: &[SimGame], format: &PlayoffFormat, simulations: usize, seed: u64) -> Vec<Odds> {
+    let mut random = Random(seed);
+    let mut base = Season::new(teams.len());
+    let mut remaining: Vec<(usize, usize)> = Vec::new();
+    for game in games {
+        let (Some(away), Some(home)) = (get_index(teams, game.away), get_index(teams, game.home)) else { continue };
+        match game.winner {
+            Some(winner) if winner == game.home => base.add_result(teams, home, away),
+            Some(_) => base.add_result(teams, away, home),
+            None => remaining.push((away, home))
+        }
+    }
+
+    let mut leagues: Vec<i32> = teams.iter().map(|team| team.league).collect();
+    leagues.sort();
+    leagues.dedup();
+
+    let mut counts: Vec<Odds> = vec![Odds::default(); teams.len()];
+    for _ in 0..simulations {
+        let mut season = base.clone();
+        for &(away, home) in &remaining {
+            if random.next_f64() < win_probability(&teams[home], &teams[away]) {
+                season.add_result(teams, home, away);
+            }
+            else {
+                season.add_result(teams, away, home);
+            }
+        }
+
+        for &league in &leagues {
+            let mut divisions: Vec<i32> = teams.iter().filter(|team| team.league == league).map(|team| team.division).collect();
+            divisions.sort();
+            divisions.dedup();
+
+            let mut winners: Vec<usize> = divisions.iter().filter_map(|&division| {
+                let group = (0..teams.len()).filter(|&team| teams[team].division == division).collect();
+                season.rank(group, &mut random).first().copied()
+            }).collect();
+            winners = season.rank(winners, &mut random);
+            for (seed, &team) in winners.iter().take(format.division_winners).enumerate() {
+                counts[team].division += 1.0;
+                if seed < format.byes {
+                    counts[team].bye += 1.0;
+                }
+            }
+
+            let others = (0..teams.len()).filter(|team| teams[*team].league == league && !winners.contains(team)).collect();
+            for &team in season.rank(others, &mut random).iter().take(format.wild_cards) {
+                counts[team].wild_card += 1.0;
+            }
+        }
+    }
+
+    let total = simulations.max(1) as f64;
+    counts.into_iter().map(|odds| Odds {
+        division: odds.division / total,
+        wild_card: odds.wild_card / total,
+        bye: odds.bye / total
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED: u64 = 7;
+
+    // One league of three divisions with two teams each, ids are 10 * division + team
+    fn league() -> Vec<SimTeam> {
+        (1..=3).flat_map(|division| (1..=2).map(move |team| SimTeam {
+            id: 10 * division + team, league: 1, division, strength: 0.5
+        })).collect()
+    }
+
+    fn played(away: i32, home: i32, winner: i32, times: usize) -> Vec<SimGame> {
+        (0..times).map(|_| SimGame { away, home, winner: Some(winner) }).collect()
+    }
+
+    fn odds_for(teams: &[SimTeam], odds: &[Odds], id: i32) -> Odds {
+        odds[get_index(teams, id).unwrap()].clone()
+    }
+
+    #[test]
+    fn even_teams_favour_the_home_team() {
+        let home = SimTeam { id: 1, league: 1, division: 1, strength: 0.5 };
+        let away = SimTeam { id: 2, league: 1, division: 1, strength: 0.5 };
+        assert!((win_probability(&home, &away) - (0.5 + HOME_FIELD_ADVANTAGE)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn stronger_team_is_favoured_on_the_road() {
+        let home = SimTeam { id: 1, league: 1, division: 1, strength: 0.4 };
+        let away = SimTeam { id: 2, league: 1, division: 1, strength: 0.6 };
+        assert!(win_probability(&home, &away) < 0.5);
+        let certain = SimTeam { id: 3, league: 1, division: 1, strength: 1.0 };
+        assert_eq!(win_probability(&certain, &away), 1.0);
+    }
+
+    #[test]
+    fn tie_is_broken_by_head_to_head() {
+        let teams = league();
+        let mut season = Season::new(teams.len());
+        // 11 and 12 finish 2-2, 11 takes the season series 2-1
+        season.add_result(&teams, 0, 1);
+        season.add_result(&teams, 0, 1);
+        season.add_result(&teams, 1, 0);
+        season.add_result(&teams, 1, 2);
+        season.add_result(&teams, 2, 0);
+        assert_eq!(season.pct(0), season.pct(1));
+        assert_eq!(season.rank(vec![1, 0], &mut Random(SEED)), vec![0, 1]);
+    }
+
+    #[test]
+    fn even_head_to_head_falls_back_to_division_record() {
+        let teams = league();
+        let mut season = Season::new(teams.len());
+        // 11 and 21 never meet and finish 1-1, 11 lost inside its division and 21 won inside its own
+        season.add_result(&teams, 1, 0);
+        season.add_result(&teams, 0, 4);
+        season.add_result(&teams, 2, 3);
+        season.add_result(&teams, 5, 2);
+        assert_eq!(season.pct(0), season.pct(2));
+        assert_eq!(season.rank(vec![0, 2], &mut Random(SEED)), vec![2, 0]);
+    }
+
+    #[test]
+    fn completed_season_is_decided() {
+        let teams = league();
+        let format = get_playoff_format(2024).unwrap();
+        let mut games: Vec<SimGame> = Vec::new();
+        // Division winners finish 3-0, 3-1 and 2-1, so the byes go to 11 and 21
+        games.extend(played(12, 11, 11, 3));
+        games.extend(played(22, 21, 21, 3));
+        games.extend(played(21, 22, 22, 1));
+        games.extend(played(32, 31, 32, 2));
+        games.extend(played(31, 32, 31, 1));
+        let odds = simulate(&teams, &games, &format, 100, SEED);
+
+        for (id, division) in [(11, 1.0), (21, 1.0), (32, 1.0), (12, 0.0), (22, 0.0), (31, 0.0)] {
+            assert_eq!(odds_for(&teams, &odds, id).division, division, "team {id}");
+        }
+        assert_eq!(odds_for(&teams, &odds, 11).bye, 1.0);
+        assert_eq!(odds_for(&teams, &odds, 21).bye, 1.0);
+        assert_eq!(odds_for(&teams, &odds, 32).bye, 0.0);
+        for id in [12, 22, 31] {
+            assert_eq!(odds_for(&teams, &odds, id).wild_card, 1.0, "team {id}");
+        }
+    }
+
+    #[test]
+    fn division_tie_goes_to_the_head_to_head_winner() {
+        let teams = league();
+        let format = get_playoff_format(2024).unwrap();
+        let mut games: Vec<SimGame> = Vec::new();
+        // 11 and 12 both finish 2-2, 11 wins their series 2-1
+        games.extend(played(12, 11, 11, 2));
+        games.extend(played(11, 12, 12, 1));
+        games.extend(played(11, 21, 21, 1));
+        games.extend(played(12, 22, 12, 1));
+        let odds = simulate(&teams, &games, &format, 100, SEED);
+
+        assert_eq!(odds_for(&teams, &odds, 11).division, 1.0);
+        assert_eq!(odds_for(&teams, &odds, 12).division, 0.0);
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_odds() {
+        let teams = league();
+        let format = get_playoff_format(2024).unwrap();
+        let games: Vec<SimGame> = teams.iter().flat_map(|home| teams.iter()
+            .filter(move |away| away.id != home.id)
+            .map(move |away| SimGame { away: away.id, home: home.id, winner: None })).collect();
+        let first = simulate(&teams, &games, &format, 500, SEED);
+        let second = simulate(&teams, &games, &format, 500, SEED);
+
+        for (first, second) in first.iter().zip(second.iter()) {
+            assert_eq!((first.division, first.wild_card, first.bye), (second.division, second.wild_card, second.bye));
+        }
+        let division_total: f64 = first.iter().map(|odds| odds.division).sum();
+        assert!((division_total - format.division_winners as f64).abs() < 1e-9);
+    }
+}
@@ -1,9 +1,15 @@
+use std::collections::HashMap;
 use serde::Deserialize;
 use reqwest::blocking::{get};
 use term_table::{row, Table};
 use term_table::row::Row;
-use term_table::table_cell::TableCell;
-use crate::query::QueryError;
+use term_table::table_cell::{Alignment, TableCell};
+use crate::chart::series_chart;
+use crate::game::{get_league_schedule, get_season_record_by_date};
+use crate::simulation::{get_playoff_format, simulate, PlayoffFormat, SimGame, SimTeam};
+use crate::sabermetrics::get_pythagorean_pct;
+use crate::query::{empty, get_query_param, get_season, split_options, terminal_width, QueryError};
+use crate::teams::get_team;
 
 #[derive(Deserialize)]
 struct Standings {
@@ -12,109 +18,610 @@ struct Standings {
 
 #[derive(Deserialize)]
 struct Division {
+    division: Option<DivisionName>,
+    league: LeagueName,
     teamRecords: Vec<Team>
 }
 
+#[derive(Deserialize)]
+struct DivisionName {
+    #[serde(default)]
+    id: i32,
+    #[serde(default)]
+    nameShort: String
+}
+
+#[derive(Deserialize)]
+struct LeagueName {
+    #[serde(default)]
+    id: i32,
+    #[serde(default)]
+    name: String
+}
+
 #[derive(Deserialize)]
 struct Team {
     team: TeamName,
+    #[serde(default)]
+    divisionLeader: bool,
+    #[serde(default)]
     streak: Streak,
     leagueRecord: Record,
     gamesBack: String,
+    #[serde(default = "no_games_back")]
     wildCardGamesBack: String,
+    #[serde(default)]
     runsScored: i32,
+    #[serde(default)]
     runsAllowed: i32,
+    #[serde(default)]
     runDifferential: i32,
+    #[serde(default)]
     records: Records
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Default)]
 struct Records {
+    #[serde(default)]
     overallRecords: Vec<Record>,
+    #[serde(default)]
     expectedRecords: Vec<Record>,
-    splitRecords: Vec<Record>
+    #[serde(default)]
+    splitRecords: Vec<Record>,
+    #[serde(default)]
+    divisionRecords: Vec<DivisionRecord>
+}
+
+#[derive(Deserialize)]
+struct DivisionRecord {
+    wins: u8,
+    losses: u8,
+    division: DivisionName
 }
 
 #[derive(Deserialize)]
 struct Record {
     wins: u8,
     losses: u8,
-    pct: String
+    #[serde(default)]
+    pct: String,
+    #[serde(default, rename = "type")]
+    recordType: String
 }
 
 #[derive(Deserialize)]
 struct TeamName {
+    id: i32,
     name: String
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Default)]
 struct Streak {
     streakCode: String
 }
 
-macro_rules! locations {
-    () => { vec!["East              ", "Central           ", "West              "] };
+macro_rules! standings_url {
+    ($league_id:expr, $season:expr, $date:expr) => {
+        format!("https://statsapi.mlb.com/api/v1/standings?leagueId={}&season={}{}&hydrate=division,league", $league_id, $season, $date)
+    };
 }
 
-macro_rules! division_header {
-    ($league:expr, $location:expr) => {
-        row!(format!("{} {}", $league, $location), "W", "L", "PCT", "GB", "WCGB", "L10", "STRK",
-        "RS", "RA", "DIFF", "X W-L", "XS W-L", "HOME", "AWAY", ">.500")
+struct Column {
+    key: &'static str,
+    header: &'static str,
+    ascending: bool,
+    value: fn(&Team, i32) -> (String, f64)
+}
+
+macro_rules! record_column {
+    ($key:expr, $header:expr, $records:ident, $record_type:expr) => {
+        Column { key: $key, header: $header, ascending: false, value: |team, _| get_record_value(&team.records.$records, $record_type) }
     };
 }
 
-fn display_league_standings(league: &str, standings: &Standings) {
-    const EXPECTED_CURR_INDEX: usize = 0;
-    const EXPECTED_SEASON_INDEX: usize = 1;
-    const HOME_RECORD_INDEX: usize = 0;
-    const AWAY_RECORD_INDEX: usize = 1;
-    const LAST_TEN_INDEX: usize = 8;
-    const WINNING_TEAMS_INDEX: usize = 11;
+macro_rules! stat_column {
+    ($key:expr, $header:expr, $ascending:expr, $stat:ident) => {
+        Column { key: $key, header: $header, ascending: $ascending, value: |team, _| (team.$stat.to_string(), team.$stat as f64) }
+    };
+}
 
-    let locations = locations!();
-    let arr: Vec<(&Division, &&str)> = standings.records.iter().zip(locations.iter()).collect();
-    for (division, location) in arr {
-        let mut table = Table::new();
-        table.add_row(division_header!(league, location));
-        for team in &division.teamRecords {
-            let expected_records = &team.records.expectedRecords;
-            let expected_curr = &expected_records[EXPECTED_CURR_INDEX];
-            let expected_season = &expected_records[EXPECTED_SEASON_INDEX];
+const COLUMNS: [Column; 29] = [
+    Column { key: "w", header: "W", ascending: false, value: |team, _| (team.leagueRecord.wins.to_string(), team.leagueRecord.wins as f64) },
+    Column { key: "l", header: "L", ascending: true, value: |team, _| (team.leagueRecord.losses.to_string(), team.leagueRecord.losses as f64) },
+    Column { key: "pct", header: "PCT", ascending: false, value: |team, _| (team.leagueRecord.pct.clone(), get_pct(team)) },
+    Column { key: "gb", header: "GB", ascending: true, value: |team, _| (team.gamesBack.clone(), parse_games_back(&team.gamesBack)) },
+    Column { key: "wcgb", header: "WCGB", ascending: true, value: |team, _| (team.wildCardGamesBack.clone(), parse_games_back(&team.wildCardGamesBack)) },
+    record_column!("l10", "L10", splitRecords, "lastTen"),
+    Column { key: "strk", header: "STRK", ascending: false, value: |team, _| (team.streak.streakCode.clone(), get_streak_value(&team.streak.streakCode)) },
+    stat_column!("rs", "RS", false, runsScored),
+    stat_column!("ra", "RA", true, runsAllowed),
+    stat_column!("diff", "DIFF", false, runDifferential),
+    Column { key: "pythag", header: "PYTHAG W-L", ascending: false, value: get_pythagorean_value },
+    record_column!("xwl", "X W-L", expectedRecords, "xWinLoss"),
+    record_column!("xswl", "XS W-L", expectedRecords, "xWinLossSeason"),
+    record_column!("home", "HOME", overallRecords, "home"),
+    record_column!("away", "AWAY", overallRecords, "away"),
+    record_column!("winners", ">.500", splitRecords, "winners"),
+    Column { key: "div", header: "DIV", ascending: false, value: |team, division_id| get_division_record_value(team, division_id) },
+    record_column!("day", "DAY", splitRecords, "day"),
+    record_column!("night", "NIGHT", splitRecords, "night"),
+    record_column!("grass", "GRASS", splitRecords, "grass"),
+    record_column!("turf", "TURF", splitRecords, "turf"),
+    record_column!("one-run", "1-RUN", splitRecords, "oneRun"),
+    record_column!("extras", "XTRA", splitRecords, "extraInning"),
+    record_column!("vl", "VS L", splitRecords, "left"),
+    record_column!("vr", "VS R", splitRecords, "right"),
+    record_column!("vl-home", "VS L HOME", splitRecords, "leftHome"),
+    record_column!("vl-away", "VS L AWAY", splitRecords, "leftAway"),
+    record_column!("vr-home", "VS R HOME", splitRecords, "rightHome"),
+    record_column!("vr-away", "VS R AWAY", splitRecords, "rightAway"),
+];
+
+const DEFAULT_COLUMNS: [&str; 15] = [
+    "w", "l", "pct", "gb", "wcgb", "l10", "strk", "rs", "ra", "diff", "xwl", "xswl", "home", "away", "winners"
+];
+
+const OVERALL_COLUMNS: [&str; 8] = ["w", "l", "pct", "rs", "ra", "diff", "pythag", "strk"];
+
+fn no_games_back() -> String {
+    "-".to_string()
+}
+
+fn record_value(wins: u8, losses: u8) -> (String, f64) {
+    let games = wins as f64 + losses as f64;
+    (format!("{}-{}", wins, losses), if games > 0.0 { wins as f64 / games } else { 0.0 })
+}
+
+fn get_record_value(records: &[Record], record_type: &str) -> (String, f64) {
+    records.iter().find(|record| record.recordType == record_type)
+        .map_or(("-".to_string(), 0.0), |record| record_value(record.wins, record.losses))
+}
+
+fn get_division_record_value(team: &Team, division_id: i32) -> (String, f64) {
+    team.records.divisionRecords.iter().find(|record| record.division.id == division_id)
+        .map_or(("-".to_string(), 0.0), |record| record_value(record.wins, record.losses))
+}
+
+fn parse_games_back(games_back: &str) -> f64 {
+    games_back.trim_start_matches('+').parse::<f64>().unwrap_or(0.0)
+}
+
+fn get_streak_value(streak_code: &str) -> f64 {
+    let length = streak_code.get(1..).and_then(|length| length.parse::<f64>().ok()).unwrap_or(0.0);
+    if streak_code.starts_with('L') { -length } else { length }
+}
+
+fn get_pythagorean_value(team: &Team, _: i32) -> (String, f64) {
+    let expected_pct = get_pythagorean_pct(team.runsScored, team.runsAllowed);
+    let games = team.leagueRecord.wins as i32 + team.leagueRecord.losses as i32;
+    let expected_wins = (expected_pct * games as f64).round() as i32;
+    (format!("{}-{}", expected_wins, games - expected_wins), expected_pct)
+}
+
+fn get_columns(options: &HashMap<String, String>, default_columns: &[&str]) -> Result<Vec<&'static Column>, QueryError> {
+    const COLUMNS_OPTION: &str = "columns";
+
+    let keys: Vec<String> = match options.get(COLUMNS_OPTION) {
+        Some(keys) => keys.split(',').map(|key| key.trim().to_ascii_lowercase()).collect(),
+        None => default_columns.iter().map(|key| key.to_string()).collect()
+    };
+    keys.iter().map(|key| COLUMNS.iter().find(|column| column.key == key)
+        .ok_or(QueryError::OptionError(COLUMNS_OPTION.to_string(), key.clone()))).collect()
+}
+
+fn get_sort_column(options: &HashMap<String, String>) -> Result<Option<&'static Column>, QueryError> {
+    const SORT_OPTION: &str = "sort";
+
+    match options.get(SORT_OPTION) {
+        Some(key) => COLUMNS.iter().find(|column| column.key == key.to_ascii_lowercase()).map(Some)
+            .ok_or(QueryError::OptionError(SORT_OPTION.to_string(), key.clone())),
+        None => Ok(None)
+    }
+}
 
-            let ballpark_records = &team.records.overallRecords;
-            let home = &ballpark_records[HOME_RECORD_INDEX];
-            let away = &ballpark_records[AWAY_RECORD_INDEX];
+fn standings_table(name: &str, teams: &[(&Team, i32)], columns: &[&Column], ranked: bool) -> Table {
+    let mut header: Vec<&str> = if ranked { vec!["#", name] } else { vec![name] };
+    header.extend(columns.iter().map(|column| column.header));
 
-            let split_records = &team.records.splitRecords;
-            let last_ten = &split_records[LAST_TEN_INDEX];
-            let winning_record_teams = &split_records[WINNING_TEAMS_INDEX];
+    let mut table = Table::new();
+    table.add_row(Row::new(header));
+    for (i, &(team, division_id)) in teams.iter().enumerate() {
+        let mut row: Vec<String> = if ranked { vec![(i + 1).to_string()] } else { Vec::new() };
+        row.push(team.team.name.clone());
+        row.extend(columns.iter().map(|column| (column.value)(team, division_id).0));
+        table.add_row(Row::new(row));
+    }
+    table
+}
 
+fn get_league_teams(standings: &Standings) -> Vec<(&Team, i32)> {
+    standings.records.iter().flat_map(|division| {
+        let division_id = division.division.as_ref().map_or(0, |division| division.id);
+        division.teamRecords.iter().map(move |team| (team, division_id))
+    }).collect()
+}
+
+fn display_ranked_standings(title: &str, mut teams: Vec<(&Team, i32)>, columns: &[&Column], sort_column: &Column) {
+    teams.sort_by(|(team0, division0), (team1, division1)| {
+        let ordering = (sort_column.value)(team0, *division0).1.total_cmp(&(sort_column.value)(team1, *division1).1);
+        if sort_column.ascending { ordering } else { ordering.reverse() }
+    });
+    println!("{}", standings_table(&format!("{title} by {}", sort_column.header), &teams, columns, true).render());
+}
+
+fn get_pct(team: &Team) -> f64 {
+    team.leagueRecord.pct.parse::<f64>().unwrap_or(0.0)
+}
+
+fn sort_by_pct(teams: &mut [&Team]) {
+    teams.sort_by(|team0, team1| get_pct(team1).total_cmp(&get_pct(team0))
+        .then(team1.leagueRecord.wins.cmp(&team0.leagueRecord.wins)));
+}
+
+// Wins by the leader or losses by the trailer needed before the trailer can no longer catch the leader
+fn magic_number(leader: &Team, trailer: &Team, remaining: &HashMap<i32, i32>) -> i32 {
+    let trailer_max_wins = trailer.leagueRecord.wins as i32 + remaining.get(&trailer.team.id).copied().unwrap_or(0);
+    (trailer_max_wins + 1 - leader.leagueRecord.wins as i32).max(0)
+}
+
+fn format_magic_number(prefix: &str, number: i32, done: &str) -> String {
+    if number == 0 { done.to_string() } else { format!("{prefix} {number}") }
+}
+
+fn get_remaining_by_team(season: i32, as_of: Option<&str>) -> reqwest::Result<HashMap<i32, i32>> {
+    let mut remaining: HashMap<i32, i32> = HashMap::new();
+    for (away_id, home_id, _) in get_league_schedule(season, as_of)?.into_iter().filter(|(_, _, winner)| winner.is_none()) {
+        *remaining.entry(away_id).or_insert(0) += 1;
+        *remaining.entry(home_id).or_insert(0) += 1;
+    }
+    Ok(remaining)
+}
+
+fn get_division_leader(division: &Division) -> Option<&Team> {
+    division.teamRecords.iter().find(|team| team.divisionLeader).or(division.teamRecords.first())
+}
+
+fn split_leaders(standings: &Standings) -> (Vec<&Team>, Vec<&Team>) {
+    let leaders: Vec<i32> = standings.records.iter().filter_map(get_division_leader).map(|team| team.team.id).collect();
+    let (mut division_leaders, mut wild_cards): (Vec<&Team>, Vec<&Team>) = standings.records.iter()
+        .flat_map(|division| division.teamRecords.iter())
+        .partition(|team| leaders.contains(&team.team.id));
+    sort_by_pct(&mut division_leaders);
+    sort_by_pct(&mut wild_cards);
+    (division_leaders, wild_cards)
+}
+
+fn display_wild_card_standings(league: &str, standings: &Standings, remaining: &HashMap<i32, i32>, format: &PlayoffFormat) {
+    const COLS: usize = 8;
+
+    let wild_card_spots = format.wild_cards;
+    let (_, wild_cards) = split_leaders(standings);
+    let leaders: HashMap<i32, &Team> = standings.records.iter().flat_map(|division| {
+        let leader = get_division_leader(division);
+        division.teamRecords.iter().filter_map(move |team| leader.map(|leader| (team.team.id, leader)))
+    }).collect();
+
+    let mut table = Table::new();
+    table.add_row(row!(format!("{league} Wild Card"), "W", "L", "PCT", "WCGB", "WC M/E", "DIV E", "Remaining"));
+    for (i, &team) in wild_cards.iter().enumerate() {
+        if i == wild_card_spots {
+            table.add_row(row!(TableCell::builder("Cut Line").col_span(COLS).alignment(Alignment::Center).build()));
+        }
+        let (wild_card_number, games_back) = match (i < wild_card_spots, wild_cards.get(wild_card_spots), wild_cards.get(wild_card_spots - 1)) {
+            (true, Some(&first_out), _) => (format_magic_number("M", magic_number(team, first_out, remaining), "Clinched"), "-".to_string()),
+            (false, _, Some(&last_in)) => {
+                let games_back = (last_in.leagueRecord.wins as f64 - team.leagueRecord.wins as f64
+                    + team.leagueRecord.losses as f64 - last_in.leagueRecord.losses as f64) / 2.0;
+                (format_magic_number("E", magic_number(last_in, team, remaining), "Eliminated"), format!("{games_back:.1}"))
+            },
+            _ => ("-".to_string(), "-".to_string())
+        };
+        let division_number = leaders.get(&team.team.id)
+            .map_or("-".to_string(), |&leader| format_magic_number("E", magic_number(leader, team, remaining), "Eliminated"));
+
+        table.add_row(row!(
+            &team.team.name, team.leagueRecord.wins, team.leagueRecord.losses, &team.leagueRecord.pct,
+            games_back, wild_card_number, division_number, remaining.get(&team.team.id).copied().unwrap_or(0)
+        ));
+    }
+    println!("{}", table.render());
+}
+
+fn display_playoff_picture(league: &str, standings: &Standings, remaining: &HashMap<i32, i32>, format: &PlayoffFormat) {
+    let (division_leaders, wild_cards) = split_leaders(standings);
+    let seeds: Vec<&Team> = division_leaders.iter().take(format.division_winners)
+        .chain(wild_cards.iter().take(format.wild_cards)).copied().collect();
+    let first_out = wild_cards.get(format.wild_cards);
+
+    let mut table = Table::new();
+    table.add_row(row!(format!("{league} Seed"), "Team", "W", "L", "PCT", "Clinch", "Round"));
+    for (i, &team) in seeds.iter().enumerate() {
+        let clinch = if i < format.division_winners {
+            let division = standings.records.iter()
+                .find(|division| division.teamRecords.iter().any(|record| record.team.id == team.team.id));
+            let division_magic = division.map_or(0, |division| division.teamRecords.iter()
+                .filter(|record| record.team.id != team.team.id)
+                .map(|record| magic_number(team, record, remaining)).max().unwrap_or(0));
+            format_magic_number("Div M", division_magic, "Division")
+        }
+        else {
+            first_out.map_or("Wild Card".to_string(), |&first_out|
+                format_magic_number("WC M", magic_number(team, first_out, remaining), "Wild Card"))
+        };
+        let round = if i < format.byes { "Bye" } else { format.first_round };
+        table.add_row(row!(i + 1, &team.team.name, team.leagueRecord.wins, team.leagueRecord.losses,
+            &team.leagueRecord.pct, clinch, round));
+    }
+    println!("{}", table.render());
+
+    // The better seed hosts the first round
+    for &(higher, lower) in format.matchups {
+        if let (Some(home), Some(away)) = (seeds.get(higher - 1), seeds.get(lower - 1)) {
+            println!("#{} {} @ #{} {}", lower, away.team.name, higher, home.team.name);
+        }
+    }
+    println!();
+}
+
+fn get_strength(team: &Team) -> f64 {
+    const DEFAULT_STRENGTH: f64 = 0.5;
+
+    team.records.expectedRecords.iter().find(|record| record.recordType == "xWinLoss")
+        .filter(|record| record.wins as i32 + record.losses as i32 > 0)
+        .map_or(DEFAULT_STRENGTH, |record| record.wins as f64 / (record.wins as f64 + record.losses as f64))
+}
+
+fn display_playoff_odds(season: i32, as_of: Option<&str>, leagues: [(&str, &Standings); 2], format: &PlayoffFormat, simulations: usize)
+    -> reqwest::Result<()> {
+    let mut teams: Vec<(&Team, SimTeam)> = Vec::new();
+    for (_, standings) in &leagues {
+        for division in &standings.records {
+            let division_id = division.division.as_ref().map_or(0, |division| division.id);
+            for team in &division.teamRecords {
+                teams.push((team, SimTeam {
+                    id: team.team.id, league: division.league.id, division: division_id, strength: get_strength(team)
+                }));
+            }
+        }
+    }
+    let games: Vec<SimGame> = get_league_schedule(season, as_of)?.into_iter()
+        .map(|(away, home, winner)| SimGame { away, home, winner }).collect();
+
+    let (records, sim_teams): (Vec<&Team>, Vec<SimTeam>) = teams.into_iter().unzip();
+    let seed = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |time| time.as_nanos() as u64);
+    let odds = simulate(&sim_teams, &games, format, simulations, seed);
+
+    let title = as_of.map_or(season.to_string(), |date| format!("as of {date}"));
+    println!("\nPlayoff Odds {} ({} simulations)\n", title, simulations);
+    for (league, standings) in leagues {
+        let league_id = standings.records.first().map_or(0, |division| division.league.id);
+        let mut league_odds: Vec<usize> = (0..sim_teams.len()).filter(|&i| sim_teams[i].league == league_id).collect();
+        league_odds.sort_by(|&i, &j| (odds[j].division + odds[j].wild_card).total_cmp(&(odds[i].division + odds[i].wild_card)));
+
+        let mut table = Table::new();
+        table.add_row(row!(format!("{league} Team"), "W", "L", "X W%", "Division", "Bye", "Wild Card", "Playoffs"));
+        for i in league_odds {
+            let (team, team_odds) = (records[i], &odds[i]);
             table.add_row(row!(
-                &team.team.name, team.leagueRecord.wins, team.leagueRecord.losses,
-                &team.leagueRecord.pct, &team.gamesBack, &team.wildCardGamesBack,
-                format!("{}-{}", last_ten.wins, last_ten.losses), &team.streak.streakCode,
-                team.runsScored, team.runsAllowed, team.runDifferential,
-                format!("{}-{}", expected_curr.wins, expected_curr.losses),
-                format!("{}-{}", expected_season.wins, expected_season.losses),
-                format!("{}-{}", home.wins, home.losses), format!("{}-{}", away.wins, away.losses),
-                format!("{}-{}", winning_record_teams.wins, winning_record_teams.losses)
+                &team.team.name, team.leagueRecord.wins, team.leagueRecord.losses, format!("{:.3}", sim_teams[i].strength),
+                format!("{:.1}%", team_odds.division * 100.0), format!("{:.1}%", team_odds.bye * 100.0),
+                format!("{:.1}%", team_odds.wild_card * 100.0), format!("{:.1}%", (team_odds.division + team_odds.wild_card) * 100.0)
             ));
         }
         println!("{}", table.render());
     }
+    Ok(())
 }
 
-pub(crate) fn display_standings() -> Result<(), QueryError> {
-    const AL_URL: &str = "https://statsapi.mlb.com/api/v1/standings?leagueId=103";
-    const NL_URL: &str = "https://statsapi.mlb.com/api/v1/standings?leagueId=104";
+fn get_trend_teams(query: &[String], options: &HashMap<String, String>, leagues: [&Standings; 2]) -> Result<Vec<(i32, String)>, QueryError> {
+    const TEAMS_INDEX: usize = 3;
+    const DIVISION_OPTION: &str = "division";
 
-    let nl_standings: Standings = get(NL_URL)?.json()?;
-    let al_standings: Standings = get(AL_URL)?.json()?;
+    if let Some(division_name) = options.get(DIVISION_OPTION) {
+        let key = division_name.to_ascii_lowercase().replace(['-', ' '], "");
+        let division = leagues.iter().flat_map(|standings| standings.records.iter()).find(|division| {
+            let name = division.division.as_ref().map_or("".to_string(), |division| division.nameShort.to_ascii_lowercase());
+            let words: Vec<&str> = name.split_whitespace().collect();
+            // Accept both "aleast" and the short "ale" form of "AL East"
+            words.concat() == key || (words.len() == 2 && format!("{}{}", words[0], &words[1][..1]) == key)
+        }).ok_or(QueryError::OptionError(DIVISION_OPTION.to_string(), division_name.clone()))?;
+        return Ok(division.teamRecords.iter().map(|team| (team.team.id, team.team.name.clone())).collect());
+    }
 
-    println!("\nMLB Standings\n\nNational League\n");
-    display_league_standings("NL", &nl_standings);
+    if query.len() <= TEAMS_INDEX {
+        return Err(QueryError::QueryTooShort("No Teams or Division Provided".to_string()));
+    }
+    query[TEAMS_INDEX..].iter().map(|abbreviation| {
+        let (entry, team_id) = get_team(&abbreviation.to_ascii_lowercase())?;
+        Ok((team_id, entry[1..entry.len() - 1].join(" ")))
+    }).collect()
+}
 
-    println!("\nAmerican League\n");
-    display_league_standings("AL", &al_standings);
+// Each team's id mapped to the ids of every team in its division, itself included
+fn get_division_teams(leagues: [&Standings; 2]) -> HashMap<i32, Vec<i32>> {
+    let mut division_teams: HashMap<i32, Vec<i32>> = HashMap::new();
+    for division in leagues.iter().flat_map(|standings| standings.records.iter()) {
+        let ids: Vec<i32> = division.teamRecords.iter().map(|team| team.team.id).collect();
+        for &id in &ids {
+            division_teams.insert(id, ids.clone());
+        }
+    }
+    division_teams
+}
+
+// Games back is measured against the division leader on each date, so it needs every team in the selected divisions
+fn display_standings_trend(teams: &[(i32, String)], season: i32, division_teams: Option<&HashMap<i32, Vec<i32>>>)
+    -> reqwest::Result<()> {
+    const CHART_HEIGHT: usize = 16;
+
+    let mut team_ids: Vec<i32> = teams.iter().map(|(team_id, _)| *team_id).collect();
+    if let Some(division_teams) = division_teams {
+        team_ids.extend(teams.iter().flat_map(|(team_id, _)| division_teams.get(team_id).into_iter().flatten()));
+        team_ids.sort();
+        team_ids.dedup();
+    }
+
+    let mut records: Vec<Vec<(String, i32)>> = Vec::with_capacity(team_ids.len());
+    for team_id in &team_ids {
+        records.push(get_season_record_by_date(*team_id, season)?);
+    }
+    let mut dates: Vec<&String> = records.iter().flatten().map(|(date, _)| date).collect();
+    dates.sort();
+    dates.dedup();
+
+    // Carry each team's record forward over the dates it did not play
+    let mut above_500: Vec<Vec<f64>> = vec![Vec::with_capacity(dates.len()); team_ids.len()];
+    let mut indices: Vec<usize> = vec![0; team_ids.len()];
+    let mut current: Vec<i32> = vec![0; team_ids.len()];
+    for &date in &dates {
+        for (i, team_records) in records.iter().enumerate() {
+            if let Some((record_date, record)) = team_records.get(indices[i]) {
+                if record_date == date {
+                    current[i] = *record;
+                    indices[i] += 1;
+                }
+            }
+            above_500[i].push(current[i] as f64);
+        }
+    }
+    let get_values = |team_id: &i32| team_ids.iter().position(|id| id == team_id).map(|i| &above_500[i]);
+
+    let values: Vec<Vec<f64>> = teams.iter().map(|(team_id, _)| {
+        let team_values = get_values(team_id).cloned().unwrap_or_default();
+        let Some(division) = division_teams.and_then(|division_teams| division_teams.get(team_id)) else { return team_values };
+        let division_values: Vec<&Vec<f64>> = division.iter().filter_map(get_values).collect();
+        team_values.iter().enumerate().map(|(day, value)| {
+            let leader = division_values.iter().map(|values| values[day]).fold(*value, f64::max);
+            -(leader - value) / 2.0
+        }).collect()
+    }).collect();
+
+    let series: Vec<(String, Vec<f64>)> = teams.iter().map(|(_, name)| name.clone()).zip(values).collect();
+    let (title, label): (&str, fn(f64) -> String) = if division_teams.is_some() {
+        ("Games Back", |value| format!("{:.1}", value.abs()))
+    }
+    else {
+        ("Games Above .500", |value| format!("{value:+.0}"))
+    };
+    match (dates.first(), dates.last()) {
+        (Some(first), Some(last)) => println!("\n{} {} ({} to {})\n", season, title, first, last),
+        _ => println!("\n{} {}\n", season, title)
+    }
+    println!("{}", series_chart(&series, CHART_HEIGHT, terminal_width(), label));
+    Ok(())
+}
+
+fn display_league_standings(standings: &Standings, columns: &[&Column]) {
+    for division in &standings.records {
+        // Divisions only exist from 1969 on, before that each league is a single table
+        let (name, division_id) = match &division.division {
+            Some(division_name) if !division_name.nameShort.is_empty() => (&division_name.nameShort, division_name.id),
+            _ => (&division.league.name, 0)
+        };
+        let teams: Vec<(&Team, i32)> = division.teamRecords.iter().map(|team| (team, division_id)).collect();
+        println!("{}", standings_table(name, &teams, columns, false).render());
+    }
+}
+
+pub(crate) fn display_standings(query: &[String]) -> Result<(), QueryError> {
+    const AL_ID: i32 = 103;
+    const NL_ID: i32 = 104;
+    const DATE_OPTION: &str = "date";
+    const YEAR_LEN: usize = 4;
+    const VIEW_INDEX: usize = 2;
+    const SIMULATIONS_OPTION: &str = "simulations";
+    const DEFAULT_SIMULATIONS: usize = 10000;
+
+    let (query, options) = split_options(query);
+    let (season, date) = match options.get(DATE_OPTION) {
+        Some(date) => {
+            let season = date.get(..YEAR_LEN).and_then(|year| year.parse::<i32>().ok())
+                .ok_or(QueryError::OptionError(DATE_OPTION.to_string(), date.clone()))?;
+            (season, format!("&date={date}"))
+        },
+        None => (get_season(&options)?, "".to_string())
+    };
+
+    let nl_standings: Standings = get(standings_url!(NL_ID, season, date))?.json()?;
+    let al_standings: Standings = get(standings_url!(AL_ID, season, date))?.json()?;
+
+    let as_of = options.get(DATE_OPTION).map_or(season.to_string(), |date| format!("as of {date}"));
+    let date = options.get(DATE_OPTION).map(|date| date.as_str());
+    let playoff_format = || get_playoff_format(season)
+        .ok_or(QueryError::OptionError("season".to_string(), format!("{season} (unsupported playoff format)")));
+    match get_query_param!(query, VIEW_INDEX, empty!()).as_str() {
+        "w" | "wildcard" => {
+            let format = playoff_format()?;
+            let remaining = get_remaining_by_team(season, date)?;
+            println!("\nWild Card Standings {}\n", as_of);
+            display_wild_card_standings("NL", &nl_standings, &remaining, &format);
+            display_wild_card_standings("AL", &al_standings, &remaining, &format);
+        },
+        "t" | "trend" => {
+            const METRIC_OPTION: &str = "metric";
+
+            let games_back = match options.get(METRIC_OPTION).map(|metric| metric.to_ascii_lowercase()).as_deref() {
+                Some("gb") => true,
+                Some("above") | None => false,
+                Some(metric) => return Err(QueryError::OptionError(METRIC_OPTION.to_string(), metric.to_string()))
+            };
+            let teams = get_trend_teams(&query, &options, [&nl_standings, &al_standings])?;
+            let division_teams = get_division_teams([&nl_standings, &al_standings]);
+            display_standings_trend(&teams, season, games_back.then_some(&division_teams))?;
+        },
+        "m" | "overall" => {
+            const LEAGUE_OPTION: &str = "league";
+            const DEFAULT_SORT: &str = "pct";
+
+            let columns = get_columns(&options, &OVERALL_COLUMNS)?;
+            let sort_column = match get_sort_column(&options)? {
+                Some(sort_column) => sort_column,
+                None => COLUMNS.iter().find(|column| column.key == DEFAULT_SORT).unwrap()
+            };
+            println!("\nOverall Standings {}\n", as_of);
+            match options.get(LEAGUE_OPTION).map(|league| league.to_ascii_lowercase()).as_deref() {
+                Some("nl") => display_ranked_standings("NL", get_league_teams(&nl_standings), &columns, sort_column),
+                Some("al") => display_ranked_standings("AL", get_league_teams(&al_standings), &columns, sort_column),
+                Some(league) => return Err(QueryError::OptionError(LEAGUE_OPTION.to_string(), league.to_string())),
+                None => {
+                    let mut teams = get_league_teams(&nl_standings);
+                    teams.append(&mut get_league_teams(&al_standings));
+                    display_ranked_standings("MLB", teams, &columns, sort_column);
+                }
+            }
+        },
+        "o" | "odds" => {
+            let simulations = match options.get(SIMULATIONS_OPTION) {
+                Some(simulations) => simulations.parse::<usize>()
+                    .map_err(|_| QueryError::OptionError(SIMULATIONS_OPTION.to_string(), simulations.clone()))?,
+                None => DEFAULT_SIMULATIONS
+            };
+            display_playoff_odds(season, date, [("NL", &nl_standings), ("AL", &al_standings)], &playoff_format()?, simulations)?;
+        },
+        "p" | "playoffs" => {
+            let format = playoff_format()?;
+            let remaining = get_remaining_by_team(season, date)?;
+            println!("\nPlayoff Picture {}\n", as_of);
+            display_playoff_picture("NL", &nl_standings, &remaining, &format);
+            display_playoff_picture("AL", &al_standings, &remaining, &format);
+        },
+        _ => {
+            let columns = get_columns(&options, &DEFAULT_COLUMNS)?;
+            println!("\nMLB Standings {}", as_of);
+            if let Some(sort_column) = get_sort_column(&options)? {
+                display_ranked_standings("NL", get_league_teams(&nl_standings), &columns, sort_column);
+                display_ranked_standings("AL", get_league_teams(&al_standings), &columns, sort_column);
+                return Ok(());
+            }
+            if !nl_standings.records.is_empty() {
+                println!("\nNational League\n");
+                display_league_standings(&nl_standings, &columns);
+            }
+            if !al_standings.records.is_empty() {
+                println!("\nAmerican League\n");
+                display_league_standings(&al_standings, &columns);
+            }
+        }
+    }
     Ok(())
 }
@@ -5,7 +5,7 @@ use std::fs::File;
 use std::io::{BufReader, Read, Seek, SeekFrom};
 use crate::hitting_stats::display_hitting_stats;
 use crate::pitching_stats::display_pitching_stats;
-use crate::query::{get_query_param, QueryError};
+use crate::query::{get_game_type, get_query_param, split_options, QueryError};
 use crate::query::QueryError::EntryError;
 
 #[derive(Deserialize)]
@@ -19,11 +19,19 @@ pub(crate) struct Split<T> {
     pub(crate) season: String,
     #[serde(default = "no_name")]
     pub(crate) player: Player,
+    pub(crate) team: Option<SplitTeam>,
     pub(crate) stat: T
 }
 
+#[derive(Deserialize)]
+pub(crate) struct SplitTeam {
+    pub(crate) id: i32
+}
+
 #[derive(Deserialize)]
 pub(crate) struct Player {
+    #[serde(default)]
+    pub(crate) id: i32,
     pub(crate) fullName: String
 }
 
@@ -32,7 +40,7 @@ fn default_season() -> String {
 }
 
 fn no_name() -> Player {
-    Player { fullName: "".to_string() }
+    Player { id: 0, fullName: "".to_string() }
 }
 
 macro_rules! database_file {
@@ -67,7 +75,7 @@ pub(crate) fn get_entry(file: &String, key: &String, id_len: usize) -> Result<Ve
     let mut end = file_len;
     let mut buffer: Box<[u8]> = vec![0; line_len as usize].into_boxed_slice();
     while start < end {
-        let mid = (start + end >> 1) / line_len * line_len;
+        let mid = ((start + end) >> 1) / line_len * line_len;
         player_file.seek(SeekFrom::Start(mid))?;
         player_file.read_exact(&mut buffer)?;
 
@@ -102,14 +110,43 @@ pub(crate) fn get_entry(file: &String, key: &String, id_len: usize) -> Result<Ve
     Err(EntryError(key.to_string()))
 }
 
-pub(crate) fn stats_query(query: &Vec<String>) -> Result<(), QueryError> {
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Role {
+    Hitter,
+    Pitcher,
+    TwoWay
+}
+
+// Splits come back empty when the player has no stats of the game type, e.g. a player who never reached the postseason
+pub(crate) fn no_stats_error(season_type: &str, game_type: &str) -> QueryError {
+    EntryError(format!("{season_type} stats with game type {game_type}"))
+}
+
+// The player's id and role, stored in the database as 0 for hitters, 1 for pitchers and 2 for two-way players
+pub(crate) fn get_player(player: &String) -> Result<(i32, Role), QueryError> {
+    const ID_LEN: usize = 6;
+    const ROLE_INDEX: usize = 1;
+    const ID_INDEX: usize = 2;
+
+    let entry= get_entry(database_file!(), player, ID_LEN)?;
+    let id = entry[ID_INDEX].parse::<i32>().unwrap();
+    let role = match entry[ROLE_INDEX].as_bytes()[0] {
+        b'0' => Role::Hitter,
+        b'2' => Role::TwoWay,
+        _ => Role::Pitcher
+    };
+    Ok((id, role))
+}
+
+pub(crate) fn stats_query(query: &[String]) -> Result<(), QueryError> {
     const PLAYER_INDEX: usize = 2;
     const SEASON_TYPE_INDEX: usize = 3;
     const MIN_LENGTH: usize = 3;
-    const ID_LEN: usize = 6;
-    const IS_PITCHER_INDEX: usize = 1;
-    const ID_INDEX: usize = 2;
+    const SABERMETRICS_OPTION: &str = "sabermetrics";
 
+    let (query, options) = split_options(query);
+    let game_type = get_game_type(&options)?;
+    let show_sabermetrics = options.contains_key(SABERMETRICS_OPTION);
     if query.len() < MIN_LENGTH  {
         return Err(QueryError::QueryTooShort("No Player Provided".to_string()));
     }
@@ -121,15 +158,12 @@ pub(crate) fn stats_query(query: &Vec<String>) -> Result<(), QueryError> {
         _ => "season"
     };
 
-    let player = &query[PLAYER_INDEX];
-    let entry= get_entry(database_file!(), player, ID_LEN)?;
-
-    let id = entry[ID_INDEX].parse::<i32>().unwrap();
-    if entry[IS_PITCHER_INDEX].as_bytes()[0] != b'0' {
-        display_pitching_stats(id, season_type)?;
+    let (id, role) = get_player(&query[PLAYER_INDEX])?;
+    if role == Role::Pitcher {
+        display_pitching_stats(id, season_type, game_type, show_sabermetrics)?;
     }
     else {
-        display_hitting_stats(id, season_type)?;
+        display_hitting_stats(id, season_type, game_type, show_sabermetrics)?;
     }
     Ok(())
 }
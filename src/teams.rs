@@ -1,14 +1,17 @@
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::{env};
 use serde::Deserialize;
 use reqwest::blocking::get;
 use term_table::{row, Table};
 use term_table::row::Row;
 use term_table::table_cell::TableCell;
-use crate::stats::{get_entry, Stat};
+use crate::stats::{get_entry, Split, Stat};
 use crate::hitting_stats::{get_basic_season_hitting_stats, get_basic_hitting_row, BasicHittingStats, basic_hitting_header, Batter, basic_hitting_row};
 use crate::pitching_stats::{get_season_pitching_stats, get_pitching_row, PitchingStats, pitching_header, Pitcher, pitching_row};
-use crate::query::{empty, get_query_param, QueryError};
+use crate::query::{empty, get_query_param, get_season, split_options, QueryError};
+use crate::sabermetrics::{format_rate, sabermetric_inputs, sabermetric_hitting_header, sabermetric_hitting_row, sabermetric_pitching_header,
+    sabermetric_pitching_row, HittingLine, PitchingLine};
 
 #[derive(Deserialize)]
 struct Roster {
@@ -32,8 +35,8 @@ struct Position {
 }
 
 #[derive(Deserialize)]
-struct TeamStats {
-    stats: (Stat<Batter>, Stat<Pitcher>)
+pub(crate) struct TeamStats {
+    pub(crate) stats: (Stat<Batter>, Stat<Pitcher>)
 }
 
 const PITCHER: &str = "P";
@@ -48,6 +51,12 @@ macro_rules! stats_url {
     };
 }
 
+macro_rules! all_teams_stats_url {
+    ($season:expr) => {
+        format!("https://statsapi.mlb.com/api/v1/teams/stats?group=hitting,pitching&stats=season&sportIds=1&season={}", $season)
+    };
+}
+
 macro_rules! database_file {
     ($file:expr) => { &format!("{}/database/{}", env!("CARGO_MANIFEST_DIR"), $file) };
 }
@@ -87,9 +96,11 @@ fn get_team_roster(team_id: i32) -> reqwest::Result<(Vec<Player>, Vec<Player>)>
     Ok(roster.roster.into_iter().partition(|player| player.position.abbreviation == PITCHER))
 }
 
-fn display_team_season_stats(team_name: String, team_id: i32, display_hitting: bool, display_pitching: bool) -> reqwest::Result<()> {
+fn display_team_season_stats(team_name: String, team_id: i32, display_hitting: bool, display_pitching: bool, show_sabermetrics: bool)
+    -> reqwest::Result<()> {
     let (pitchers, hitters) = get_team_roster(team_id)?;
     let team_stats: TeamStats = get(stats_url!(team_id))?.json()?;
+    let (league_constants, mut park_factors) = sabermetric_inputs(show_sabermetrics);
 
     if display_hitting {
         let mut stat_table: Table = stat_table!(BasicHittingStats, basic_hitting_header, hitters,
@@ -97,13 +108,30 @@ fn display_team_season_stats(team_name: String, team_id: i32, display_hitting: b
         let split = &team_stats.stats.0.splits[0];
         stat_table.add_row(basic_hitting_row!("Team", &split.stat));
         println!("\n{}Hitting Stats\n\n{}", team_name, stat_table.render());
+
+        if show_sabermetrics {
+            let mut sabermetric_table = Table::new();
+            sabermetric_table.add_row(sabermetric_hitting_header!("Season"));
+            sabermetric_table.add_row(sabermetric_hitting_row(&split.season, &HittingLine::from(&split.stat),
+                league_constants.get(&split.season), park_factors.get(&split.season, team_id)));
+            println!("{}", sabermetric_table.render());
+        }
     }
 
     if display_pitching {
         let mut stat_table: Table = stat_table!(PitchingStats, pitching_header, pitchers,
             get_season_pitching_stats, get_pitching_row, pitcher_comparator);
-        stat_table.add_row(pitching_row!("Team", &team_stats.stats.1.splits[0].stat));
+        let split = &team_stats.stats.1.splits[0];
+        stat_table.add_row(pitching_row!("Team", &split.stat));
         println!("\n{}Pitching Stats\n\n{}", team_name, stat_table.render());
+
+        if show_sabermetrics {
+            let mut sabermetric_table = Table::new();
+            sabermetric_table.add_row(sabermetric_pitching_header!("Season"));
+            sabermetric_table.add_row(sabermetric_pitching_row(&split.season, &PitchingLine::from(&split.stat),
+                league_constants.get(&split.season), park_factors.get(&split.season, team_id)));
+            println!("{}", sabermetric_table.render());
+        }
     }
     Ok(())
 }
@@ -116,11 +144,191 @@ pub(crate) fn get_team(abbreviation: &String) -> Result<(Vec<String>, i32), Quer
     Ok((entry, team_id))
 }
 
-pub(crate) fn display_team_stats(query: &Vec<String>) -> Result<(), QueryError> {
+// Each line of the team database is the abbreviation, the team name and the id
+fn get_teams() -> Result<Vec<(String, i32)>, QueryError> {
+    let teams = std::fs::read_to_string(database_file!("team_ids.txt"))?;
+    Ok(teams.lines().filter_map(|line| {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let team_id = words.last()?.parse::<i32>().ok()?;
+        Some((words[1..words.len() - 1].join(" "), team_id))
+    }).collect())
+}
+
+// Each team's name with its season totals
+pub(crate) type NamedTeamStats<T> = Vec<(String, T)>;
+
+// Splits of the league wide team stats named after the team database, teams missing from it are left out
+fn name_team_splits<T>(splits: Vec<Split<T>>, names: &HashMap<i32, String>) -> NamedTeamStats<T> {
+    splits.into_iter().filter_map(|split| Some((names.get(&split.team?.id)?.clone(), split.stat))).collect()
+}
+
+// Season hitting and pitching totals for every team in the team database, from a single request
+pub(crate) fn get_all_team_stats(season: i32) -> Result<(NamedTeamStats<Batter>, NamedTeamStats<Pitcher>), QueryError> {
+    let names: HashMap<i32, String> = get_teams()?.into_iter().map(|(name, team_id)| (team_id, name)).collect();
+    let team_stats: TeamStats = get(all_teams_stats_url!(season))?.json()?;
+    let (hitting, pitching) = team_stats.stats;
+    Ok((name_team_splits(hitting.splits, &names), name_team_splits(pitching.splits, &names)))
+}
+
+struct Column<T> {
+    key: &'static str,
+    header: &'static str,
+    ascending: bool,
+    value: fn(&T) -> (String, f64)
+}
+
+macro_rules! count_column {
+    ($key:expr, $header:expr, $ascending:expr, $stat:ident) => {
+        Column { key: $key, header: $header, ascending: $ascending, value: |stat| (stat.$stat.to_string(), stat.$stat as f64) }
+    };
+}
+
+macro_rules! rate_column {
+    ($key:expr, $header:expr, $ascending:expr, $stat:ident) => {
+        Column { key: $key, header: $header, ascending: $ascending, value: |stat| (stat.$stat.clone(), stat.$stat.parse::<f64>().unwrap_or(0.0)) }
+    };
+}
+
+const HITTING_COLUMNS: [Column<Batter>; 15] = [
+    count_column!("r", "R", false, runs),
+    count_column!("h", "H", false, hits),
+    count_column!("2b", "2B", false, doubles),
+    count_column!("3b", "3B", false, triples),
+    count_column!("hr", "HR", false, homeRuns),
+    count_column!("rbi", "RBI", false, rbi),
+    count_column!("bb", "BB", false, baseOnBalls),
+    count_column!("k", "K", true, strikeOuts),
+    count_column!("sb", "SB", false, stolenBases),
+    count_column!("lob", "LOB", true, leftOnBase),
+    rate_column!("avg", "AVG", false, avg),
+    rate_column!("obp", "OBP", false, obp),
+    rate_column!("slg", "SLG", false, slg),
+    rate_column!("ops", "OPS", false, ops),
+    Column { key: "iso", header: "ISO", ascending: false, value: |stat| {
+        let iso = HittingLine::from(stat).iso();
+        (format_rate(iso), iso.unwrap_or(0.0))
+    } }
+];
+
+const PITCHING_COLUMNS: [Column<Pitcher>; 12] = [
+    rate_column!("era", "ERA", true, era),
+    rate_column!("whip", "WHIP", true, whip),
+    count_column!("r", "R", true, runs),
+    count_column!("h", "H", true, hits),
+    count_column!("hr", "HR", true, homeRuns),
+    count_column!("bb", "BB", true, baseOnBalls),
+    count_column!("k", "K", false, strikeOuts),
+    rate_column!("k9", "K/9", false, strikeoutsPer9Inn),
+    rate_column!("bb9", "BB/9", true, walksPer9Inn),
+    rate_column!("avg", "AVG", true, avg),
+    count_column!("sv", "SV", false, saves),
+    Column { key: "der", header: "DER", ascending: false, value: get_defensive_efficiency }
+];
+
+// Share of balls in play turned into outs, leaving out home runs
+fn get_defensive_efficiency(stat: &Pitcher) -> (String, f64) {
+    let balls_in_play = stat.battersFaced - stat.strikeOuts - stat.baseOnBalls - stat.hitByPitch - stat.homeRuns;
+    let efficiency = if balls_in_play > 0 {
+        Some(1.0 - (stat.hits - stat.homeRuns) as f64 / balls_in_play as f64)
+    } else { None };
+    (format_rate(efficiency), efficiency.unwrap_or(0.0))
+}
+
+fn get_sort_column<'a, T>(sort_key: Option<&String>, columns: &'a [Column<T>]) -> Option<&'a Column<T>> {
+    match sort_key {
+        Some(key) => columns.iter().find(|column| column.key == key.to_ascii_lowercase()),
+        None => columns.first()
+    }
+}
+
+// The hitting and pitching columns to sort by, for the groups that are shown
+type SortColumns = (Option<&'static Column<Batter>>, Option<&'static Column<Pitcher>>);
+
+// With both groups shown the sort key only has to belong to one of them, the other keeps its default sort
+fn get_sort_columns(options: &HashMap<String, String>, display_hitting: bool, display_pitching: bool)
+    -> Result<SortColumns, QueryError> {
+    const SORT_OPTION: &str = "sort";
+
+    let sort_key = options.get(SORT_OPTION);
+    let hitting_sort = if display_hitting { get_sort_column(sort_key, &HITTING_COLUMNS) } else { None };
+    let pitching_sort = if display_pitching { get_sort_column(sort_key, &PITCHING_COLUMNS) } else { None };
+    if let (Some(key), None, None) = (sort_key, hitting_sort, pitching_sort) {
+        return Err(QueryError::OptionError(SORT_OPTION.to_string(), key.clone()));
+    }
+    Ok((
+        hitting_sort.or(if display_hitting { HITTING_COLUMNS.first() } else { None }),
+        pitching_sort.or(if display_pitching { PITCHING_COLUMNS.first() } else { None })
+    ))
+}
+
+// Teams tied on a stat share the best rank, so three teams tied for first are all "1"
+fn get_ranks(values: &[f64], ascending: bool) -> Vec<usize> {
+    values.iter().map(|value| 1 + values.iter().filter(|other| if ascending { *other < value } else { *other > value }).count())
+        .collect()
+}
+
+fn display_ranked_team_stats<T>(title: &str, teams: &[(&str, &T)], columns: &[Column<T>], sort_column: &Column<T>) {
+    let ranks: Vec<Vec<usize>> = columns.iter().map(|column| {
+        let values: Vec<f64> = teams.iter().map(|(_, stat)| (column.value)(stat).1).collect();
+        get_ranks(&values, column.ascending)
+    }).collect();
+
+    let mut order: Vec<usize> = (0..teams.len()).collect();
+    order.sort_by(|&i, &j| {
+        let ordering = (sort_column.value)(teams[i].1).1.total_cmp(&(sort_column.value)(teams[j].1).1);
+        if sort_column.ascending { ordering } else { ordering.reverse() }
+    });
+
+    let mut header: Vec<&str> = vec!["#", title];
+    header.extend(columns.iter().map(|column| column.header));
+    let mut table = Table::new();
+    table.add_row(Row::new(header));
+    for (i, &team) in order.iter().enumerate() {
+        let (name, stat) = teams[team];
+        let mut row: Vec<String> = vec![(i + 1).to_string(), name.to_string()];
+        row.extend(columns.iter().zip(ranks.iter()).map(|(column, ranks)| format!("{} ({})", (column.value)(stat).0, ranks[team])));
+        table.add_row(Row::new(row));
+    }
+    println!("\n{}", table.render());
+}
+
+fn display_all_team_stats(options: &HashMap<String, String>, display_hitting: bool, display_pitching: bool) -> Result<(), QueryError> {
+    let season = get_season(options)?;
+    let (hitting_sort, pitching_sort) = get_sort_columns(options, display_hitting, display_pitching)?;
+
+    let (hitting, pitching) = get_all_team_stats(season)?;
+    if let Some(sort_column) = hitting_sort {
+        let teams: Vec<(&str, &Batter)> = hitting.iter().map(|(name, stat)| (name.as_str(), stat)).collect();
+        display_ranked_team_stats(&format!("{season} Hitting by {}", sort_column.header), &teams, &HITTING_COLUMNS, sort_column);
+    }
+    if let Some(sort_column) = pitching_sort {
+        let teams: Vec<(&str, &Pitcher)> = pitching.iter().map(|(name, stat)| (name.as_str(), stat)).collect();
+        display_ranked_team_stats(&format!("{season} Pitching by {}", sort_column.header), &teams, &PITCHING_COLUMNS, sort_column);
+    }
+    Ok(())
+}
+
+fn get_stat_groups(query: &[String], index: usize) -> (bool, bool) {
+    match get_query_param!(query, index, empty!()).as_str() {
+        "h" | "hitting" => (true, false),
+        "p" | "pitching" => (false, true),
+        _ => (true, true)
+    }
+}
+
+pub(crate) fn display_team_stats(query: &[String]) -> Result<(), QueryError> {
     const TEAM_INDEX: usize = 2;
     const STAT_INDEX: usize = 3;
     const MIN_LENGTH: usize = 3;
+    const ALL_OPTION: &str = "all";
+    const SABERMETRICS_OPTION: &str = "sabermetrics";
 
+    let (query, options) = split_options(query);
+    if options.contains_key(ALL_OPTION) {
+        // Without a team the stat group follows the command directly
+        let (display_hitting, display_pitching) = get_stat_groups(&query, TEAM_INDEX);
+        return display_all_team_stats(&options, display_hitting, display_pitching);
+    }
     if query.len() < MIN_LENGTH {
         return Err(QueryError::QueryTooShort("No Team Provided".to_string()));
     }
@@ -128,17 +336,45 @@ pub(crate) fn display_team_stats(query: &Vec<String>) -> Result<(), QueryError>
     let team = &query[TEAM_INDEX];
     let (entry, team_id) = get_team(team)?;
     let mut name: String = "".to_string();
-    for i in 1..(entry.len() - 1) {
-        name.push_str(&format!("{} ", entry[i].clone()));
+    for word in &entry[1..(entry.len() - 1)] {
+        name.push_str(&format!("{} ", word));
     }
 
-    let (display_hitting, display_pitching) =
-        match get_query_param!(query, STAT_INDEX, empty!()).as_str() {
-            "h" | "hitting" => (true, false),
-            "p" | "pitching" => (false, true),
-            _ => (true, true)
-        };
-
-    display_team_season_stats(name, team_id, display_hitting, display_pitching)?;
+    let (display_hitting, display_pitching) = get_stat_groups(&query, STAT_INDEX);
+    display_team_season_stats(name, team_id, display_hitting, display_pitching, options.contains_key(SABERMETRICS_OPTION))?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sort_option(key: &str) -> HashMap<String, String> {
+        HashMap::from([("sort".to_string(), key.to_string())])
+    }
+
+    fn keys(options: &HashMap<String, String>, display_hitting: bool, display_pitching: bool)
+        -> (Option<&'static str>, Option<&'static str>) {
+        let (hitting, pitching) = get_sort_columns(options, display_hitting, display_pitching).unwrap();
+        (hitting.map(|column| column.key), pitching.map(|column| column.key))
+    }
+
+    #[test]
+    fn groups_default_to_their_first_column() {
+        assert_eq!(keys(&HashMap::new(), true, true), (Some("r"), Some("era")));
+        assert_eq!(keys(&HashMap::new(), false, true), (None, Some("era")));
+    }
+
+    #[test]
+    fn key_of_one_group_leaves_the_other_on_its_default() {
+        assert_eq!(keys(&sort_option("ERA"), true, true), (Some("r"), Some("era")));
+        assert_eq!(keys(&sort_option("ops"), true, true), (Some("ops"), Some("era")));
+        assert_eq!(keys(&sort_option("hr"), true, true), (Some("hr"), Some("hr")));
+    }
+
+    #[test]
+    fn key_must_belong_to_a_shown_group() {
+        assert!(get_sort_columns(&sort_option("era"), true, false).is_err());
+        assert!(get_sort_columns(&sort_option("xyz"), true, true).is_err());
+    }
+}